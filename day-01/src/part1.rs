@@ -1,15 +1,13 @@
-pub fn order_elves(data: &str) -> Vec<u32> {
-    let mut rows: Vec<u32> = data
-        .split("\n\n")
-        .map(|r| r.lines().map(|l| l.parse::<u32>().unwrap()).sum())
-        .collect();
+use std::io::BufRead;
 
-    rows.sort_unstable();
-    rows
-}
+use crate::stream::top_elves;
 
-pub fn max_elf(elves: &[u32]) -> u32 {
-    *elves.last().unwrap()
+/// Calories carried by the elf with the most
+pub fn max_elf(input: impl BufRead) -> u64 {
+    top_elves(input, 1)
+        .unwrap()
+        .first()
+        .map_or(0, |elf| elf.calories)
 }
 
 #[cfg(test)]
@@ -18,7 +16,7 @@ pub mod tests {
 
     #[test]
     fn test_example() {
-        let elves = order_elves(include_str!("../test.txt"));
-        assert_eq!(max_elf(&elves), 24000);
+        let input = include_str!("../test.txt");
+        assert_eq!(max_elf(input.as_bytes()), 24000);
     }
 }
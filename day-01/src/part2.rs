@@ -1,14 +1,22 @@
-pub fn top_three_elves(elves: &[u32]) -> u32 {
-    elves.iter().rev().take(3).sum()
+use std::io::BufRead;
+
+use crate::stream::top_elves;
+
+pub fn top_three_elves(input: impl BufRead) -> u64 {
+    top_elves(input, 3)
+        .unwrap()
+        .iter()
+        .map(|elf| elf.calories)
+        .sum()
 }
 
 #[cfg(test)]
 pub mod tests {
-    use crate::{part1::order_elves, part2::*};
+    use crate::part2::*;
 
     #[test]
     fn test_example() {
-        let elves = order_elves(include_str!("../test.txt"));
-        assert_eq!(top_three_elves(&elves), 45000);
+        let input = include_str!("../test.txt");
+        assert_eq!(top_three_elves(input.as_bytes()), 45000);
     }
 }
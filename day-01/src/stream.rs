@@ -0,0 +1,151 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    io::{self, BufRead},
+};
+
+/// Total calories carried by an elf, with its position in the input
+/// (starting from 0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elf {
+    pub index: usize,
+    pub calories: u64,
+}
+
+/**
+ * Keeps the `k` elves with the most calories, in a min-heap of at most `k`
+ * entries whose root is the weakest one kept. On equal calories the elf
+ * that came first wins.
+ */
+#[derive(Debug, Clone)]
+pub struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<(u64, Reverse<usize>)>>,
+}
+
+impl TopK {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    pub fn push(&mut self, elf: Elf) {
+        self.heap.push(Reverse((elf.calories, Reverse(elf.index))));
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    /// The elves kept, from the one with the most calories
+    pub fn into_sorted(self) -> Vec<Elf> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((calories, Reverse(index)))| Elf { index, calories })
+            .collect()
+    }
+}
+
+/// Reads the calories list line by line, calling `on_elf` with the total of
+/// each elf. Any number of blank lines separates elves, and `\r\n` line
+/// endings are accepted.
+pub fn read_elves(mut input: impl BufRead, mut on_elf: impl FnMut(Elf)) -> io::Result<()> {
+    let mut line = String::new();
+    let mut line_number = 0;
+    let mut index = 0;
+    let mut current: Option<u64> = None;
+
+    loop {
+        line.clear();
+        let read = input.read_line(&mut line)?;
+        line_number += 1;
+        let item = line.trim_end_matches(['\n', '\r']);
+
+        if read == 0 || item.trim().is_empty() {
+            if let Some(calories) = current.take() {
+                on_elf(Elf { index, calories });
+                index += 1;
+            }
+            if read == 0 {
+                return Ok(());
+            }
+            continue;
+        }
+
+        let calories = item.trim().parse::<u64>().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: invalid calories '{}': {}", line_number, item, e),
+            )
+        })?;
+        current = Some(current.unwrap_or(0) + calories);
+    }
+}
+
+/// The `k` elves carrying the most calories, from the first
+pub fn top_elves(input: impl BufRead, k: usize) -> io::Result<Vec<Elf>> {
+    let mut top = TopK::new(k);
+    read_elves(input, |elf| top.push(elf))?;
+    Ok(top.into_sorted())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::stream::*;
+
+    #[test]
+    fn test_top_elves() {
+        let input = include_str!("../test.txt");
+        let top = top_elves(input.as_bytes(), 3).unwrap();
+        assert_eq!(
+            top,
+            vec![
+                Elf {
+                    index: 3,
+                    calories: 24000
+                },
+                Elf {
+                    index: 2,
+                    calories: 11000
+                },
+                Elf {
+                    index: 4,
+                    calories: 10000
+                },
+            ]
+        );
+        assert_eq!(top_elves(input.as_bytes(), 10).unwrap().len(), 5);
+        assert!(top_elves(input.as_bytes(), 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let input = "\r\n1\r\n2\r\n\r\n\r\n5\r\n\r\n3\r\n\r\n\r\n";
+        let top = top_elves(input.as_bytes(), 2).unwrap();
+        // Ties go to the first elf
+        assert_eq!(
+            top,
+            vec![
+                Elf {
+                    index: 1,
+                    calories: 5
+                },
+                Elf {
+                    index: 0,
+                    calories: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        let error = top_elves("1\n2\n\nabc\n".as_bytes(), 1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error
+            .to_string()
+            .starts_with("line 4: invalid calories 'abc'"));
+    }
+}
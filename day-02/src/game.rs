@@ -0,0 +1,359 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use itertools::Itertools;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    fn parse(name: &str) -> Result<Self, GameError> {
+        match name {
+            "Lose" => Ok(Outcome::Lose),
+            "Draw" => Ok(Outcome::Draw),
+            "Win" => Ok(Outcome::Win),
+            _ => Err(GameError::UnknownOutcome(name.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    MissingField(&'static str),
+    InvalidLine(String),
+    InvalidNumber(String),
+    UnknownMove(String),
+    UnknownOutcome(String),
+    UnknownSymbol(String),
+    /// The moves, scores and outcomes don't add up
+    Inconsistent(String),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::MissingField(field) => write!(f, "missing field '{}'", field),
+            GameError::InvalidLine(line) => write!(f, "invalid line '{}'", line),
+            GameError::InvalidNumber(n) => write!(f, "invalid number '{}'", n),
+            GameError::UnknownMove(m) => write!(f, "unknown move '{}'", m),
+            GameError::UnknownOutcome(o) => write!(f, "unknown outcome '{}'", o),
+            GameError::UnknownSymbol(s) => write!(f, "unknown symbol '{}'", s),
+            GameError::Inconsistent(reason) => write!(f, "inconsistent game: {}", reason),
+        }
+    }
+}
+
+impl Error for GameError {}
+
+/// The move (and its total score) that scores the most against a given
+/// distribution of opponent moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BestResponse {
+    pub player: usize,
+    pub score: usize,
+}
+
+/**
+ * A Rock-Paper-Scissors-like game loaded from a definition such as
+ * `rps.txt`: the moves, their scores, the scores for losing, drawing and
+ * winning, which move beats which, and the symbols used in the strategy
+ * guides. Moves are referred to by their index in `moves`.
+ */
+#[derive(Debug, Clone)]
+pub struct Game {
+    pub moves: Vec<String>,
+    pub move_scores: Vec<usize>,
+    /// Scores for losing, drawing and winning
+    pub outcome_scores: [usize; 3],
+    /// Outcome for the row move played against the column move
+    pub matrix: Vec<Vec<Outcome>>,
+    pub opponent_symbols: HashMap<String, usize>,
+    pub player_symbols: HashMap<String, usize>,
+    pub outcome_symbols: HashMap<String, Outcome>,
+}
+
+impl Game {
+    pub fn parse(definition: &str) -> Result<Self, GameError> {
+        let fields: HashMap<&str, &str> = definition
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| {
+                l.split_once(':')
+                    .map(|(k, v)| (k.trim(), v.trim()))
+                    .ok_or_else(|| GameError::InvalidLine(l.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        let field = |name: &'static str| {
+            fields
+                .get(name)
+                .map(|v| v.split_whitespace().collect_vec())
+                .ok_or(GameError::MissingField(name))
+        };
+        let numbers = |name: &'static str| -> Result<Vec<usize>, GameError> {
+            field(name)?
+                .into_iter()
+                .map(|n| {
+                    n.parse()
+                        .map_err(|_| GameError::InvalidNumber(n.to_string()))
+                })
+                .collect()
+        };
+
+        let moves = field("moves")?
+            .into_iter()
+            .map(|m| m.to_string())
+            .collect_vec();
+        let index = |name: &str| {
+            moves
+                .iter()
+                .position(|m| m == name)
+                .ok_or_else(|| GameError::UnknownMove(name.to_string()))
+        };
+
+        let move_scores = numbers("scores")?;
+        if move_scores.len() != moves.len() {
+            return Err(GameError::Inconsistent(format!(
+                "{} moves but {} scores",
+                moves.len(),
+                move_scores.len()
+            )));
+        }
+        let outcome_scores: [usize; 3] = numbers("outcomes")?
+            .try_into()
+            .map_err(|_| GameError::Inconsistent("expected 3 outcome scores".to_string()))?;
+
+        let mut outcomes = vec![vec![None; moves.len()]; moves.len()];
+        for (i, row) in outcomes.iter_mut().enumerate() {
+            row[i] = Some(Outcome::Draw);
+        }
+        for pair in field("beats")? {
+            let (winner, loser) = pair
+                .split_once('>')
+                .ok_or_else(|| GameError::InvalidLine(pair.to_string()))?;
+            let (winner, loser) = (index(winner)?, index(loser)?);
+            if winner == loser || outcomes[winner][loser] == Some(Outcome::Lose) {
+                return Err(GameError::Inconsistent(pair.to_string()));
+            }
+            outcomes[winner][loser] = Some(Outcome::Win);
+            outcomes[loser][winner] = Some(Outcome::Lose);
+        }
+        let matrix = outcomes
+            .into_iter()
+            .enumerate()
+            .map(|(player, row)| {
+                row.into_iter()
+                    .enumerate()
+                    .map(|(opponent, outcome)| {
+                        outcome.ok_or_else(|| {
+                            GameError::Inconsistent(format!(
+                                "no winner between {} and {}",
+                                moves[player], moves[opponent]
+                            ))
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        let symbols = |name: &'static str| -> Result<Vec<(String, &str)>, GameError> {
+            field(name)?
+                .into_iter()
+                .map(|s| {
+                    s.split_once('=')
+                        .map(|(k, v)| (k.to_string(), v))
+                        .ok_or_else(|| GameError::InvalidLine(s.to_string()))
+                })
+                .collect()
+        };
+        let move_symbols = |name: &'static str| -> Result<HashMap<String, usize>, GameError> {
+            symbols(name)?
+                .into_iter()
+                .map(|(k, v)| Ok((k, index(v)?)))
+                .collect()
+        };
+        let opponent_symbols = move_symbols("opponent")?;
+        let player_symbols = move_symbols("player")?;
+        let outcome_symbols = symbols("results")?
+            .into_iter()
+            .map(|(k, v)| Ok((k, Outcome::parse(v)?)))
+            .collect::<Result<_, GameError>>()?;
+
+        Ok(Self {
+            moves,
+            move_scores,
+            outcome_scores,
+            matrix,
+            opponent_symbols,
+            player_symbols,
+            outcome_symbols,
+        })
+    }
+
+    pub fn rock_paper_scissors() -> Self {
+        Self::parse(include_str!("../rps.txt")).unwrap()
+    }
+
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        Self::parse(include_str!("../rpsls.txt")).unwrap()
+    }
+
+    fn lookup<T: Copy>(symbols: &HashMap<String, T>, symbol: &str) -> Result<T, GameError> {
+        symbols
+            .get(symbol)
+            .copied()
+            .ok_or_else(|| GameError::UnknownSymbol(symbol.to_string()))
+    }
+
+    pub fn opponent_move(&self, symbol: &str) -> Result<usize, GameError> {
+        Self::lookup(&self.opponent_symbols, symbol)
+    }
+
+    pub fn player_move(&self, symbol: &str) -> Result<usize, GameError> {
+        Self::lookup(&self.player_symbols, symbol)
+    }
+
+    pub fn outcome(&self, symbol: &str) -> Result<Outcome, GameError> {
+        Self::lookup(&self.outcome_symbols, symbol)
+    }
+
+    /// Points for playing `player` against `opponent`
+    pub fn score(&self, player: usize, opponent: usize) -> usize {
+        let outcome = self.matrix[player][opponent];
+        self.move_scores[player] + self.outcome_score(outcome)
+    }
+
+    /// Points for losing, drawing or winning
+    pub fn outcome_score(&self, outcome: Outcome) -> usize {
+        match outcome {
+            Outcome::Lose => self.outcome_scores[0],
+            Outcome::Draw => self.outcome_scores[1],
+            Outcome::Win => self.outcome_scores[2],
+        }
+    }
+
+    /// The first move getting `outcome` against `opponent`
+    pub fn move_for(&self, opponent: usize, outcome: Outcome) -> Option<usize> {
+        (0..self.moves.len()).find(|&m| self.matrix[m][opponent] == outcome)
+    }
+
+    /// How many times each opponent move is played
+    pub fn frequencies(
+        &self,
+        opponents: impl IntoIterator<Item = usize>,
+    ) -> Result<Vec<usize>, GameError> {
+        let mut frequencies = vec![0; self.moves.len()];
+        for m in opponents {
+            *frequencies
+                .get_mut(m)
+                .ok_or_else(|| GameError::UnknownMove(m.to_string()))? += 1;
+        }
+        Ok(frequencies)
+    }
+
+    /// The single move to always play against opponent moves played with
+    /// the given frequencies, preferring the first move on ties
+    pub fn best_response(&self, frequencies: &[usize]) -> BestResponse {
+        (0..self.moves.len())
+            .map(|player| BestResponse {
+                player,
+                score: frequencies
+                    .iter()
+                    .enumerate()
+                    .map(|(opponent, count)| count * self.score(player, opponent))
+                    .sum(),
+            })
+            .rev()
+            .max_by_key(|r| r.score)
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::game::*;
+
+    #[test]
+    fn test_rock_paper_scissors() {
+        let game = Game::rock_paper_scissors();
+        let (rock, paper) = (
+            game.opponent_move("A").unwrap(),
+            game.player_move("Y").unwrap(),
+        );
+        assert_eq!(game.score(paper, rock), 8);
+        assert_eq!(game.score(rock, paper), 1);
+        assert_eq!(game.move_for(rock, Outcome::Draw), Some(rock));
+        assert_eq!(game.outcome("Z"), Ok(Outcome::Win));
+    }
+
+    #[test]
+    fn test_lizard_spock() {
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let spock = game.player_move("Z").unwrap();
+        let rock = game.opponent_move("A").unwrap();
+        let lizard = game.opponent_move("D").unwrap();
+        assert_eq!(game.matrix[spock][rock], Outcome::Win);
+        assert_eq!(game.matrix[spock][lizard], Outcome::Lose);
+        // Every move beats exactly two others
+        for row in game.matrix.iter() {
+            assert_eq!(row.iter().filter(|o| **o == Outcome::Win).count(), 2);
+        }
+    }
+
+    #[test]
+    fn test_best_response() {
+        let game = Game::rock_paper_scissors();
+        let opponents = include_str!("../test.txt")
+            .lines()
+            .map(|l| game.opponent_move(&l[0..1]).unwrap());
+        let frequencies = game.frequencies(opponents).unwrap();
+        assert_eq!(frequencies, vec![1, 1, 1]);
+        assert_eq!(
+            game.frequencies([0, 3]),
+            Err(GameError::UnknownMove("3".to_string()))
+        );
+        // Against one of each, scissors scores the most for the shape
+        assert_eq!(
+            game.best_response(&frequencies),
+            BestResponse {
+                player: 2,
+                score: 18
+            }
+        );
+        assert_eq!(game.best_response(&[3, 0, 0]).player, 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Game::parse("moves: A B\nscores: 1").unwrap_err(),
+            GameError::Inconsistent("2 moves but 1 scores".to_string())
+        );
+        let definition = include_str!("../rps.txt").replace("Rock>Scissors", "Rock>Stone");
+        assert_eq!(
+            Game::parse(&definition).unwrap_err(),
+            GameError::UnknownMove("Stone".to_string())
+        );
+        let game = Game::rock_paper_scissors();
+        assert_eq!(
+            game.opponent_move("Q"),
+            Err(GameError::UnknownSymbol("Q".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_incomplete_beats() {
+        let missing = include_str!("../rps.txt").replace(" Scissors>Paper", "");
+        assert_eq!(
+            Game::parse(&missing).unwrap_err(),
+            GameError::Inconsistent("no winner between Paper and Scissors".to_string())
+        );
+        let both_ways = include_str!("../rps.txt").replace("beats:", "beats: Paper>Scissors");
+        assert_eq!(
+            Game::parse(&both_ways).unwrap_err(),
+            GameError::Inconsistent("Scissors>Paper".to_string())
+        );
+    }
+}
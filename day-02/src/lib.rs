@@ -1,2 +1,3 @@
+pub mod game;
 pub mod part1;
 pub mod part2;
@@ -1,14 +1,27 @@
 use day_02::{
+    game::Game,
     part1::{parse, winning_score},
-    part2::transform_strategy,
+    part2::transform_strategies,
 };
 use runner::Runner;
 
 fn main() {
     let runner = Runner::start();
-    let input = parse(include_str!("../input.txt"));
-    println!("Winning score is: {}", winning_score(&input));
-    let transformed: Vec<_> = input.iter().map(transform_strategy).collect();
-    println!("Definitive score is: {}", winning_score(&transformed));
+    let game = Game::rock_paper_scissors();
+    let input = include_str!("../input.txt");
+    println!("Winning score is: {}", winning_score(&game, &parse(input)));
+    println!(
+        "Definitive score is: {}",
+        winning_score(&game, &transform_strategies(input))
+    );
+
+    let frequencies = game
+        .frequencies(parse(input).iter().map(|s| s.player1))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let best = game.best_response(&frequencies);
+    println!(
+        "Best response is always {} for {} points",
+        game.moves[best.player], best.score
+    );
     runner.end();
 }
@@ -1,57 +1,47 @@
+use crate::game::{Game, GameError};
+
+/// A round, as the indexes of the moves in the game
 #[derive(Debug)]
 pub struct Strategy {
     pub player1: usize,
     pub player2: usize,
 }
 
-fn parse_value(data: &str) -> usize {
-    match data {
-        "A" => 0,
-        "B" => 1,
-        "C" => 2,
-        "X" => 0,
-        "Y" => 1,
-        "Z" => 2,
-        _ => panic!("Unknown value: {}", data),
-    }
-}
-
-pub fn parse(data: &str) -> Vec<Strategy> {
+/// Splits each line of a strategy guide in its two symbols
+pub fn parse_columns(data: &str) -> Result<Vec<(&str, &str)>, GameError> {
     data.lines()
+        .filter(|l| !l.trim().is_empty())
         .map(|l| {
-            let mut parts = l.split(' ');
-            Strategy {
-                player1: parse_value(parts.next().unwrap()),
-                player2: parse_value(parts.next().unwrap()),
-            }
+            l.split_once(' ')
+                .map(|(a, b)| (a.trim(), b.trim()))
+                .ok_or_else(|| GameError::InvalidLine(l.to_string()))
         })
         .collect()
 }
 
-// 0 Sasso
-// 1 Carta
-// 2 Forbice
-pub fn strategy_score(strategy: &Strategy) -> usize {
-    if strategy.player1 == strategy.player2 {
-        return 3 + strategy.player2 + 1;
-    }
-
-    let is_win = match strategy.player2 {
-        0 => strategy.player1 == 2,
-        1 => strategy.player1 == 0,
-        2 => strategy.player1 == 1,
-        _ => panic!("Unknown value: {}", strategy.player2),
-    };
+/// Reads the second column as the move to play
+pub fn parse_with(game: &Game, data: &str) -> Result<Vec<Strategy>, GameError> {
+    parse_columns(data)?
+        .into_iter()
+        .map(|(opponent, player)| {
+            Ok(Strategy {
+                player1: game.opponent_move(opponent)?,
+                player2: game.player_move(player)?,
+            })
+        })
+        .collect()
+}
 
-    if is_win {
-        return 6 + strategy.player2 + 1;
-    }
+pub fn parse(data: &str) -> Vec<Strategy> {
+    parse_with(&Game::rock_paper_scissors(), data).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    strategy.player2 + 1 // + 0
+pub fn strategy_score(game: &Game, strategy: &Strategy) -> usize {
+    game.score(strategy.player2, strategy.player1)
 }
 
-pub fn winning_score(strategies: &[Strategy]) -> usize {
-    strategies.iter().map(strategy_score).sum()
+pub fn winning_score(game: &Game, strategies: &[Strategy]) -> usize {
+    strategies.iter().map(|s| strategy_score(game, s)).sum()
 }
 
 #[cfg(test)]
@@ -64,12 +54,20 @@ pub mod tests {
             player1: 0,
             player2: 1,
         };
-        assert_eq!(strategy_score(&strategy), 8);
+        assert_eq!(strategy_score(&Game::rock_paper_scissors(), &strategy), 8);
     }
 
     #[test]
     fn test_example() {
         let strategies = parse(include_str!("../test.txt"));
-        assert_eq!(winning_score(&strategies), 15);
+        assert_eq!(winning_score(&Game::rock_paper_scissors(), &strategies), 15);
+    }
+
+    #[test]
+    fn test_lizard_spock() {
+        let game = Game::rock_paper_scissors_lizard_spock();
+        // Spock vaporizes rock, lizard poisons Spock
+        let strategies = parse_with(&game, "A Z\nE Y").unwrap();
+        assert_eq!(winning_score(&game, &strategies), (5 + 6) + (4 + 6));
     }
 }
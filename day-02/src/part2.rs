@@ -1,48 +1,40 @@
-use crate::part1::Strategy;
+use crate::{
+    game::{Game, GameError},
+    part1::{parse_columns, Strategy},
+};
 
-impl Strategy {
-    const ROCK: usize = 0;
-    const PAPER: usize = 1;
-    const SCISSORS: usize = 2;
-
-    const LOSE: usize = 0;
-    const DRAW: usize = 1;
-    const WIN: usize = 2;
+/// Reads the second column as the outcome to get, choosing the move
+pub fn parse_outcomes(game: &Game, data: &str) -> Result<Vec<Strategy>, GameError> {
+    parse_columns(data)?
+        .into_iter()
+        .map(|(opponent, result)| {
+            let opponent = game.opponent_move(opponent)?;
+            let outcome = game.outcome(result)?;
+            let player = game.move_for(opponent, outcome).ok_or_else(|| {
+                GameError::Inconsistent(format!(
+                    "no move to {:?} against {}",
+                    outcome, game.moves[opponent]
+                ))
+            })?;
+            Ok(Strategy {
+                player1: opponent,
+                player2: player,
+            })
+        })
+        .collect()
 }
 
-pub fn find_shape(strategy: &Strategy) -> usize {
-    match (strategy.player1, strategy.player2) {
-        (Strategy::ROCK, Strategy::WIN) => Strategy::PAPER,
-        (Strategy::PAPER, Strategy::WIN) => Strategy::SCISSORS,
-        (Strategy::SCISSORS, Strategy::WIN) => Strategy::ROCK,
-        (Strategy::ROCK, Strategy::LOSE) => Strategy::SCISSORS,
-        (Strategy::PAPER, Strategy::LOSE) => Strategy::ROCK,
-        (Strategy::SCISSORS, Strategy::LOSE) => Strategy::PAPER,
-        (p1, Strategy::DRAW) => p1,
-        _ => panic!("Unknown strategy: {:?}", strategy),
-    }
-}
-
-pub fn transform_strategy(strategy: &Strategy) -> Strategy {
-    Strategy {
-        player1: strategy.player1,
-        player2: find_shape(strategy),
-    }
+pub fn transform_strategies(data: &str) -> Vec<Strategy> {
+    parse_outcomes(&Game::rock_paper_scissors(), data).unwrap_or_else(|e| panic!("{}", e))
 }
 
 #[cfg(test)]
 pub mod tests {
-    use crate::{
-        part1::{parse, winning_score},
-        part2::*,
-    };
+    use crate::{part1::winning_score, part2::*};
 
     #[test]
     fn test_example() {
-        let input: Vec<_> = parse(include_str!("../test.txt"))
-            .iter()
-            .map(transform_strategy)
-            .collect();
-        assert_eq!(winning_score(&input), 12);
+        let input = transform_strategies(include_str!("../test.txt"));
+        assert_eq!(winning_score(&Game::rock_paper_scissors(), &input), 12);
     }
 }
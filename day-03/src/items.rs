@@ -0,0 +1,222 @@
+use std::{
+    error::Error,
+    fmt,
+    ops::{BitAnd, BitOr},
+    str::FromStr,
+};
+
+use itertools::Itertools;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemError {
+    InvalidItem(char),
+    /// The rucksack can't be split in that many compartments of equal size
+    UnevenSplit {
+        len: usize,
+        compartments: usize,
+    },
+    EmptyGroup,
+    NoCommonItem,
+    MultipleCommonItems(Vec<char>),
+}
+
+impl fmt::Display for ItemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemError::InvalidItem(c) => write!(f, "invalid item '{}'", c),
+            ItemError::UnevenSplit { len, compartments } => write!(
+                f,
+                "can't split {} items in {} compartments",
+                len, compartments
+            ),
+            ItemError::EmptyGroup => write!(f, "empty group"),
+            ItemError::NoCommonItem => write!(f, "no common item"),
+            ItemError::MultipleCommonItems(items) => {
+                write!(f, "several common items: {}", items.iter().join(", "))
+            }
+        }
+    }
+}
+
+impl Error for ItemError {}
+
+/**
+ * A set of items, one bit per item type: bit 0 is `a` up to bit 51 for
+ * `Z`, so the bit index is the priority minus one.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    /// Every item type
+    pub const ALL: ItemSet = ItemSet((1 << 52) - 1);
+
+    pub fn bit(item: char) -> Result<u32, ItemError> {
+        match item {
+            'a'..='z' => Ok(item as u32 - 'a' as u32),
+            'A'..='Z' => Ok(item as u32 - 'A' as u32 + 26),
+            _ => Err(ItemError::InvalidItem(item)),
+        }
+    }
+
+    pub fn insert(&mut self, item: char) -> Result<(), ItemError> {
+        self.0 |= 1 << Self::bit(item)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, item: char) -> bool {
+        Self::bit(item).is_ok_and(|bit| self.0 & (1 << bit) != 0)
+    }
+
+    pub fn intersection(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    pub fn union(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Items in priority order
+    pub fn items(&self) -> impl Iterator<Item = char> + '_ {
+        ('a'..='z')
+            .chain('A'..='Z')
+            .filter(move |c| self.contains(*c))
+    }
+
+    /// The only item of the set
+    pub fn single(&self) -> Result<char, ItemError> {
+        match self.len() {
+            0 => Err(ItemError::NoCommonItem),
+            1 => Ok(self.items().next().unwrap()),
+            _ => Err(ItemError::MultipleCommonItems(self.items().collect())),
+        }
+    }
+}
+
+impl FromStr for ItemSet {
+    type Err = ItemError;
+
+    fn from_str(items: &str) -> Result<Self, Self::Err> {
+        let mut set = ItemSet::default();
+        for item in items.chars() {
+            set.insert(item)?;
+        }
+        Ok(set)
+    }
+}
+
+impl BitAnd for ItemSet {
+    type Output = ItemSet;
+
+    fn bitand(self, other: ItemSet) -> ItemSet {
+        self.intersection(&other)
+    }
+}
+
+impl BitOr for ItemSet {
+    type Output = ItemSet;
+
+    fn bitor(self, other: ItemSet) -> ItemSet {
+        self.union(&other)
+    }
+}
+
+/// Splits a rucksack in `count` compartments of the same size
+pub fn compartments(rucksack: &str, count: usize) -> Result<Vec<ItemSet>, ItemError> {
+    let len = rucksack.chars().count();
+    if count == 0 || !len.is_multiple_of(count) {
+        return Err(ItemError::UnevenSplit {
+            len,
+            compartments: count,
+        });
+    }
+    rucksack
+        .chars()
+        .chunks(len / count)
+        .into_iter()
+        .map(|chunk| chunk.collect::<String>().parse())
+        .collect()
+}
+
+/// The single item found in every set
+pub fn common_item(sets: &[ItemSet]) -> Result<char, ItemError> {
+    if sets.is_empty() {
+        return Err(ItemError::EmptyGroup);
+    }
+    sets.iter()
+        .fold(ItemSet::ALL, |common, set| common & *set)
+        .single()
+}
+
+/// The item found in every compartment of the rucksack
+pub fn misplaced_item(rucksack: &str, count: usize) -> Result<char, ItemError> {
+    common_item(&compartments(rucksack, count)?)
+}
+
+/// The badge of every group of `size` rucksacks, the last group can be
+/// smaller
+pub fn badges(data: &str, size: usize) -> Result<Vec<char>, ItemError> {
+    if size == 0 {
+        return Err(ItemError::EmptyGroup);
+    }
+    data.lines()
+        .chunks(size)
+        .into_iter()
+        .map(|group| {
+            let sets: Vec<ItemSet> = group.map(str::parse).try_collect()?;
+            common_item(&sets)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::items::*;
+
+    #[test]
+    fn test_item_set() {
+        let set: ItemSet = "vJrwpWtwJgWrhcsFMMfFFhFp".parse().unwrap();
+        assert!(set.contains('p') && set.contains('J'));
+        assert!(!set.contains('a'));
+        let other: ItemSet = "pZ".parse().unwrap();
+        assert_eq!((set & other).single(), Ok('p'));
+        assert_eq!((set | other).len(), set.len() + 1);
+        assert_eq!(ItemSet::ALL.len(), 52);
+        assert_eq!("a1".parse::<ItemSet>(), Err(ItemError::InvalidItem('1')));
+    }
+
+    #[test]
+    fn test_compartments() {
+        assert_eq!(misplaced_item("vJrwpWtwJgWrhcsFMMfFFhFp", 2), Ok('p'));
+        // The halves of `abcb` are `ab` and `cb`, only `b` is shared
+        assert_eq!(misplaced_item("abcb", 2), Ok('b'));
+        assert_eq!(misplaced_item("abcabd", 3), Err(ItemError::NoCommonItem));
+        assert_eq!(misplaced_item("xaxbxc", 3), Ok('x'));
+        assert_eq!(
+            misplaced_item("abc", 2),
+            Err(ItemError::UnevenSplit {
+                len: 3,
+                compartments: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_badges() {
+        let input = include_str!("../test.txt");
+        assert_eq!(badges(input, 3), Ok(vec!['r', 'Z']));
+        assert_eq!(
+            badges("abc\nbcd", 2),
+            Err(ItemError::MultipleCommonItems(vec!['b', 'c']))
+        );
+        assert_eq!(badges(input, 0), Err(ItemError::EmptyGroup));
+    }
+}
@@ -1,41 +1,14 @@
-use core::panic;
-
-use itertools::Itertools;
-
-// #[derive(Debug)]
-// pub struct Rucksack {
-//     pub data: Vec<char>,
-//     // pub second: Vec<char>,
-// }
-
-// impl Rucksack {
-//     fn parse(data: &str) -> Rucksack {
-//         let parts = &data.chars().chunks(data.len() / 2);
-//         let mut iter = parts.into_iter();
-//         Rucksack {
-//             first: iter.next().unwrap().collect_vec(),
-//             second: iter.next().unwrap().collect_vec()
-//         }
-//     }
-// }
+use crate::items::misplaced_item;
 
+/// The item found in both halves of the rucksack
 pub fn find_common_letters(data: &str) -> char {
-    let first = data[0..(data.len() / 2 + 1)].chars(); //.sorted();
-    let second = data[(data.len() / 2)..].chars(); // .sorted();
-
-    for (a, b) in first.cartesian_product(second) {
-        if a == b {
-            return a;
-        }
-    }
-
-    panic!("No common letters found");
+    misplaced_item(data, 2).unwrap_or_else(|e| panic!("{}", e))
 }
 
 pub fn char_priority(char: char) -> usize {
     match char {
-        char if ('a'..='z').contains(&char) => char as usize - 'a' as usize + 1,
-        char if ('A'..='Z').contains(&char) => char as usize - 'A' as usize + 27,
+        char if char.is_ascii_lowercase() => char as usize - 'a' as usize + 1,
+        char if char.is_ascii_uppercase() => char as usize - 'A' as usize + 27,
         _ => panic!("Invalid char"),
     }
 }
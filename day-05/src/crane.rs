@@ -0,0 +1,240 @@
+use std::{error::Error, fmt};
+
+use itertools::Itertools;
+
+use crate::part1::Move;
+
+/// Crate labels, from the bottom of the stack
+pub type Stack = Vec<String>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CraneError {
+    InvalidDrawing(String),
+    InvalidMove(String),
+    UnknownStack { mv: Move, stacks: usize },
+    SameStack(Move),
+    NotEnoughCrates { mv: Move, available: usize },
+}
+
+impl fmt::Display for CraneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CraneError::InvalidDrawing(reason) => write!(f, "Invalid drawing: {}", reason),
+            CraneError::InvalidMove(line) => write!(f, "Invalid move: '{}'", line),
+            CraneError::UnknownStack { mv, stacks } => {
+                write!(f, "{}: there are only {} stacks", mv, stacks)
+            }
+            CraneError::SameStack(mv) => write!(f, "{}: crates are already there", mv),
+            CraneError::NotEnoughCrates { mv, available } => write!(
+                f,
+                "{}: stack {} has only {} crates",
+                mv,
+                mv.from + 1,
+                available
+            ),
+        }
+    }
+}
+
+impl Error for CraneError {}
+
+/// Checks that a move can be applied to `stacks`
+pub fn validate(stacks: &[Stack], mv: &Move) -> Result<(), CraneError> {
+    if mv.from >= stacks.len() || mv.to >= stacks.len() {
+        return Err(CraneError::UnknownStack {
+            mv: mv.clone(),
+            stacks: stacks.len(),
+        });
+    }
+    if mv.from == mv.to {
+        return Err(CraneError::SameStack(mv.clone()));
+    }
+    let available = stacks[mv.from].len();
+    if mv.amount > available {
+        return Err(CraneError::NotEnoughCrates {
+            mv: mv.clone(),
+            available,
+        });
+    }
+    Ok(())
+}
+
+/**
+ * A crane model. A move is split in lifts of at most `capacity` crates,
+ * and every lift keeps the order of the crates it picks.
+ */
+pub trait Crane {
+    /// Most crates moved in a single lift, `None` when unlimited
+    fn capacity(&self) -> Option<usize>;
+
+    fn apply(&self, stacks: &mut [Stack], mv: &Move) -> Result<(), CraneError> {
+        validate(stacks, mv)?;
+        let mut remaining = mv.amount;
+        while remaining > 0 {
+            let lift = self.capacity().map_or(remaining, |c| c.min(remaining));
+            let from_len = stacks[mv.from].len();
+            let mut lifted = stacks[mv.from].drain(from_len - lift..).collect_vec();
+            stacks[mv.to].append(&mut lifted);
+            remaining -= lift;
+        }
+        Ok(())
+    }
+
+    /// Applies the moves in order, stopping at the first invalid one
+    fn apply_all(&self, stacks: &mut [Stack], moves: &[Move]) -> Result<(), CraneError> {
+        moves.iter().try_for_each(|mv| self.apply(stacks, mv))
+    }
+}
+
+/// Moves one crate at a time
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn capacity(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+/// Moves any number of crates at once
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Moves at most `max_lift` crates at once
+pub struct CustomCrane {
+    max_lift: usize,
+}
+
+impl CustomCrane {
+    pub fn new(max_lift: usize) -> Self {
+        assert!(max_lift > 0, "A crane must lift at least a crate");
+        Self { max_lift }
+    }
+}
+
+impl Crane for CustomCrane {
+    fn capacity(&self) -> Option<usize> {
+        Some(self.max_lift)
+    }
+}
+
+/// Draws the stacks as in the puzzle input, with every cell as wide as the
+/// longest label (or stack number).
+pub fn render(stacks: &[Stack]) -> String {
+    let width = stacks
+        .iter()
+        .flatten()
+        .map(|label| label.len())
+        .chain([stacks.len().to_string().len()])
+        .max()
+        .unwrap();
+    let height = stacks.iter().map(|s| s.len()).max().unwrap_or(0);
+
+    let mut lines = (0..height)
+        .rev()
+        .map(|y| {
+            stacks
+                .iter()
+                .map(|s| match s.get(y) {
+                    Some(label) => format!("[{:^width$}]", label),
+                    None => " ".repeat(width + 2),
+                })
+                .join(" ")
+                .trim_end()
+                .to_string()
+        })
+        .collect_vec();
+    lines.push(
+        (1..=stacks.len())
+            .map(|n| format!(" {:^width$} ", n))
+            .join(" ")
+            .trim_end()
+            .to_string(),
+    );
+    lines.join("\n")
+}
+
+/// Applies the moves one by one, returning the drawing before the first
+/// move and after each of them.
+pub fn replay(
+    crane: &impl Crane,
+    stacks: &mut [Stack],
+    moves: &[Move],
+) -> Result<Vec<String>, CraneError> {
+    let mut frames = vec![render(stacks)];
+    for mv in moves {
+        crane.apply(stacks, mv)?;
+        frames.push(render(stacks));
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        crane::*,
+        part1::{parse, top},
+    };
+
+    #[test]
+    fn test_custom_crane() {
+        let (stacks, moves) = parse(include_str!("../test.txt")).unwrap();
+
+        let mut single = stacks.clone();
+        CustomCrane::new(1).apply_all(&mut single, &moves).unwrap();
+        assert_eq!(top(&single), "CMZ");
+
+        // The second move lifts 3 crates, as two lifts of 2 and 1
+        let mut double = stacks;
+        CustomCrane::new(2).apply_all(&mut double, &moves).unwrap();
+        assert_eq!(double[2], vec!["P", "N", "D", "Z"]);
+    }
+
+    #[test]
+    fn test_validation() {
+        let (mut stacks, _) = parse(include_str!("../test.txt")).unwrap();
+        let mv = Move {
+            from: 2,
+            to: 0,
+            amount: 2,
+        };
+        let error = CrateMover9001.apply(&mut stacks, &mv).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "move 2 from 3 to 1: stack 3 has only 1 crates"
+        );
+
+        let mv = Move {
+            from: 0,
+            to: 3,
+            amount: 1,
+        };
+        assert_eq!(
+            CrateMover9000
+                .apply(&mut stacks, &mv)
+                .unwrap_err()
+                .to_string(),
+            "move 1 from 1 to 4: there are only 3 stacks"
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let input = include_str!("../test.txt");
+        let (mut stacks, moves) = parse(input).unwrap();
+        let frames = replay(&CrateMover9000, &mut stacks, &moves).unwrap();
+        assert_eq!(frames.len(), moves.len() + 1);
+
+        let drawing = input.split_once("\n\n").unwrap().0;
+        let trimmed = drawing.lines().map(|l| l.trim_end()).join("\n");
+        assert_eq!(frames[0], trimmed);
+        assert_eq!(
+            frames.last().unwrap(),
+            "        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3"
+        );
+    }
+}
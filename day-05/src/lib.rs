@@ -1,2 +1,3 @@
+pub mod crane;
 pub mod part1;
 pub mod part2;
@@ -1,70 +1,126 @@
+use std::fmt;
+
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
 
-fn reorder(stacks: &mut [Vec<char>], rules: &[Move]) {
-    for rule in rules {
-        let stack_from_len = stacks[rule.from].len();
-        let mut to_move = stacks[rule.from]
-            .drain((stack_from_len - rule.amount)..)
-            .rev()
-            .collect_vec();
-        stacks[rule.to].append(&mut to_move);
-    }
-}
+use crate::crane::{Crane, CraneError, CrateMover9000, Stack};
 
 pub fn calculate_top(data: &str) -> String {
-    let (mut stacks, rules) = parse(data);
-    reorder(&mut stacks, &rules);
-    let top = stacks.iter().map(|s| s.last().unwrap_or(&' ')).join("");
-    top
+    let (mut stacks, rules) = parse(data).unwrap_or_else(|e| panic!("{}", e));
+    CrateMover9000
+        .apply_all(&mut stacks, &rules)
+        .unwrap_or_else(|e| panic!("{}", e));
+    top(&stacks)
+}
+
+/// Labels of the top crates, with a space for empty stacks
+pub fn top(stacks: &[Stack]) -> String {
+    stacks
+        .iter()
+        .map(|s| s.last().map_or(" ", |label| label.as_str()))
+        .join("")
 }
 
-pub fn parse(data: &str) -> (Vec<Vec<char>>, Vec<Move>) {
-    let (raw_stacks, raw_rules) = data.split_once("\n\n").unwrap();
+pub fn parse(data: &str) -> Result<(Vec<Stack>, Vec<Move>), CraneError> {
+    let data = data.replace("\r\n", "\n");
+    let (raw_stacks, raw_rules) = data
+        .split_once("\n\n")
+        .ok_or_else(|| CraneError::InvalidDrawing("missing moves".to_string()))?;
 
-    let stacks = parse_stacks(raw_stacks);
-    let rules = parse_rules(raw_rules);
-    (stacks, rules)
+    let stacks = parse_stacks(raw_stacks)?;
+    let rules = parse_rules(raw_rules)?;
+    Ok((stacks, rules))
 }
 
-fn parse_stacks(data: &str) -> Vec<Vec<char>> {
-    let matrix = data
-        .lines()
-        .map(|l| l.chars().skip(1).step_by(4).collect::<Vec<_>>())
-        .collect_vec();
-    let stacks_count = matrix[0].len();
-    let mut stacks: Vec<Vec<char>> = vec![vec![]; stacks_count];
-    for row in matrix.iter().rev().skip(1) {
-        for (i, c) in row.iter().enumerate() {
-            if ' ' != *c {
-                stacks[i].push(*c);
+/// Parses the drawing, where labels can be any width: each crate goes to the
+/// stack whose number is closest to the middle of the crate.
+pub fn parse_stacks(data: &str) -> Result<Vec<Stack>, CraneError> {
+    let lines = data.lines().collect_vec();
+    let (numbers, rows) = lines
+        .split_last()
+        .ok_or_else(|| CraneError::InvalidDrawing("empty drawing".to_string()))?;
+
+    let mut columns = vec![];
+    let mut start = None;
+    for (i, c) in numbers.char_indices().chain([(numbers.len(), ' ')]) {
+        match (c.is_ascii_digit(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                columns.push((s + i - 1) / 2);
+                start = None;
+            }
+            (false, None) if c != ' ' => {
+                return Err(CraneError::InvalidDrawing(format!(
+                    "unexpected '{}' in stack numbers",
+                    c
+                )))
             }
+            _ => {}
         }
     }
-    stacks
+    if columns.is_empty() {
+        return Err(CraneError::InvalidDrawing("no stack numbers".to_string()));
+    }
+
+    let mut stacks: Vec<Stack> = vec![vec![]; columns.len()];
+    for row in rows.iter().rev() {
+        let mut rest = *row;
+        let mut offset = 0;
+        while let Some(open) = rest.find('[') {
+            let close = rest[open..].find(']').ok_or_else(|| {
+                CraneError::InvalidDrawing(format!("unclosed crate in '{}'", row))
+            })? + open;
+            let middle = offset + (open + close) / 2;
+            let stack = columns
+                .iter()
+                .position_min_by_key(|&&c| c.abs_diff(middle))
+                .unwrap();
+            stacks[stack].push(rest[open + 1..close].to_string());
+            offset += close + 1;
+            rest = &rest[close + 1..];
+        }
+    }
+    Ok(stacks)
 }
 
-#[derive(Debug)]
+/// A move, with 0-based stack indexes
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Move {
     pub from: usize,
     pub to: usize,
     pub amount: usize,
 }
 
-fn parse_rules(data: &str) -> Vec<Move> {
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.amount,
+            self.from + 1,
+            self.to + 1
+        )
+    }
+}
+
+fn parse_rules(data: &str) -> Result<Vec<Move>, CraneError> {
     lazy_static! {
-        static ref MOVE_RE: Regex = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
+        static ref MOVE_RE: Regex = Regex::new(r"^move (\d+) from (\d+) to (\d+)$").unwrap();
     }
 
     data.lines()
+        .filter(|line| !line.trim().is_empty())
         .map(|line| {
-            let captures = MOVE_RE.captures(line).unwrap();
-            Move {
-                amount: captures[1].parse().unwrap(),
-                from: captures[2].parse::<usize>().unwrap() - 1,
-                to: captures[3].parse::<usize>().unwrap() - 1,
-            }
+            let invalid = || CraneError::InvalidMove(line.to_string());
+            let captures = MOVE_RE.captures(line.trim()).ok_or_else(invalid)?;
+            let number = |i: usize| captures[i].parse::<usize>().map_err(|_| invalid());
+            let stack = |i: usize| number(i)?.checked_sub(1).ok_or_else(invalid);
+            Ok(Move {
+                amount: number(1)?,
+                from: stack(2)?,
+                to: stack(3)?,
+            })
         })
         .collect()
 }
@@ -78,4 +134,27 @@ pub mod tests {
         let top = calculate_top(include_str!("../test.txt"));
         assert_eq!(top, "CMZ");
     }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("[A]\n 1 \n\nmove 1 from 0 to 1"),
+            Err(CraneError::InvalidMove("move 1 from 0 to 1".to_string()))
+        );
+        assert_eq!(
+            parse("[A\n 1 \n\nmove 1 from 1 to 1"),
+            Err(CraneError::InvalidDrawing(
+                "unclosed crate in '[A'".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_wide_labels() {
+        let drawing = "        [Fe]\n[Cu]    [Au]\n  1   2   3";
+        assert_eq!(
+            parse_stacks(drawing).unwrap(),
+            vec![vec!["Cu"], vec![], vec!["Au", "Fe"]]
+        );
+    }
 }
@@ -1,22 +1,14 @@
-use itertools::Itertools;
-
-use crate::part1::{parse, Move};
-
-fn reorder(stacks: &mut [Vec<char>], rules: &[Move]) {
-    for rule in rules {
-        let stack_from_len = stacks[rule.from].len();
-        let mut to_move = stacks[rule.from]
-            .drain((stack_from_len - rule.amount)..)
-            .collect_vec();
-        stacks[rule.to].append(&mut to_move);
-    }
-}
+use crate::{
+    crane::{Crane, CrateMover9001},
+    part1::{parse, top},
+};
 
 pub fn calculate_top_9001(data: &str) -> String {
-    let (mut stacks, rules) = parse(data);
-    reorder(&mut stacks, &rules);
-    let top = stacks.iter().map(|s| s.last().unwrap_or(&' ')).join("");
-    top
+    let (mut stacks, rules) = parse(data).unwrap_or_else(|e| panic!("{}", e));
+    CrateMover9001
+        .apply_all(&mut stacks, &rules)
+        .unwrap_or_else(|e| panic!("{}", e));
+    top(&stacks)
 }
 
 #[cfg(test)]
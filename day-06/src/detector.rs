@@ -0,0 +1,115 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufReader, Read},
+};
+
+/**
+ * Finds markers, runs of `size` distinct characters, one character at a
+ * time. The window keeps how many times each byte appears in it and how
+ * many bytes appear more than once, so every character is O(1) whatever
+ * the window size.
+ */
+#[derive(Debug, Clone)]
+pub struct MarkerDetector {
+    pub size: usize,
+    counts: [usize; 256],
+    window: VecDeque<u8>,
+    /// Distinct bytes appearing more than once in the window
+    repeated: usize,
+    /// Characters read so far
+    position: usize,
+}
+
+impl MarkerDetector {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "Marker size can't be zero");
+        Self {
+            size,
+            counts: [0; 256],
+            window: VecDeque::with_capacity(size + 1),
+            repeated: 0,
+            position: 0,
+        }
+    }
+
+    /// Adds a character, returning the number of characters read so far if
+    /// they end with a marker. Line breaks aren't part of the signal and are
+    /// skipped.
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        if byte == b'\n' || byte == b'\r' {
+            return None;
+        }
+        self.position += 1;
+        self.window.push_back(byte);
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.repeated += 1;
+        }
+        if self.window.len() > self.size {
+            let old = self.window.pop_front().unwrap() as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 1 {
+                self.repeated -= 1;
+            }
+        }
+
+        (self.window.len() == self.size && self.repeated == 0).then_some(self.position)
+    }
+}
+
+/// Characters to read up to the end of the first marker of `size`
+/// characters, if there is one
+pub fn first_marker(reader: impl Read, size: usize) -> io::Result<Option<usize>> {
+    let mut detector = MarkerDetector::new(size);
+    for byte in BufReader::new(reader).bytes() {
+        if let Some(position) = detector.push(byte?) {
+            return Ok(Some(position));
+        }
+    }
+    Ok(None)
+}
+
+/// End positions of every window of `size` distinct characters
+pub fn all_markers(reader: impl Read, size: usize) -> io::Result<Vec<usize>> {
+    let mut detector = MarkerDetector::new(size);
+    let mut markers = vec![];
+    for byte in BufReader::new(reader).bytes() {
+        markers.extend(detector.push(byte?));
+    }
+    Ok(markers)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::detector::*;
+
+    #[test]
+    fn test_first_marker() {
+        let marker = |s: &str, size| first_marker(s.as_bytes(), size).unwrap();
+        assert_eq!(marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4), Some(7));
+        assert_eq!(marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), Some(19));
+        assert_eq!(marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb\n", 1), Some(1));
+        assert_eq!(marker("aaaa\n", 2), None);
+        assert_eq!(marker("", 4), None);
+    }
+
+    #[test]
+    fn test_all_markers() {
+        assert_eq!(all_markers("abcabba".as_bytes(), 3).unwrap(), vec![3, 4, 5]);
+        assert_eq!(all_markers("aab\r\nc".as_bytes(), 3).unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        let stream = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsgnznrnfrfntjfm";
+        for size in 1..8 {
+            let naive = (size..=stream.len())
+                .filter(|&end| {
+                    let window = &stream.as_bytes()[end - size..end];
+                    (1..size).all(|i| !window[..i].contains(&window[i]))
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(all_markers(stream.as_bytes(), size).unwrap(), naive);
+        }
+    }
+}
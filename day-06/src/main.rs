@@ -1,10 +1,16 @@
-use day_06::{part1::marker_position, part2::message_position};
+use std::fs::File;
+
+use day_06::detector::first_marker;
 use runner::Runner;
 
 fn main() {
     let runner = Runner::start();
-    let input = include_str!("../input.txt");
-    println!("Marker position is: {}", marker_position(input));
-    println!("Message position is: {}", message_position(input));
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/input.txt");
+    let position = |size| match first_marker(File::open(path).unwrap(), size).unwrap() {
+        Some(position) => position.to_string(),
+        None => "not found".to_string(),
+    };
+    println!("Marker position is: {}", position(4));
+    println!("Message position is: {}", position(14));
     runner.end();
 }
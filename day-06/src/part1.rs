@@ -1,12 +1,8 @@
-use itertools::Itertools;
+use crate::detector::first_marker;
 
-pub fn marker_position(stream: &str) -> usize {
-    stream
-        .as_bytes()
-        .windows(4)
-        .position(|w| w.iter().unique().collect_vec().len() == 4)
-        .unwrap()
-        + 4
+/// Characters to read before the start-of-packet marker, if any
+pub fn marker_position(stream: &str) -> Option<usize> {
+    first_marker(stream.as_bytes(), 4).unwrap()
 }
 
 #[cfg(test)]
@@ -15,10 +11,17 @@ pub mod tests {
 
     #[test]
     fn test_example_marker() {
-        assert_eq!(marker_position("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), 7);
-        assert_eq!(marker_position("bvwbjplbgvbhsrlpgdmjqwftvncz"), 5);
-        assert_eq!(marker_position("nppdvjthqldpwncqszvftbrmjlhg"), 6);
-        assert_eq!(marker_position("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 10);
-        assert_eq!(marker_position("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 11);
+        assert_eq!(marker_position("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Some(7));
+        assert_eq!(marker_position("bvwbjplbgvbhsrlpgdmjqwftvncz"), Some(5));
+        assert_eq!(marker_position("nppdvjthqldpwncqszvftbrmjlhg"), Some(6));
+        assert_eq!(
+            marker_position("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"),
+            Some(10)
+        );
+        assert_eq!(
+            marker_position("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"),
+            Some(11)
+        );
+        assert_eq!(marker_position("abcabc"), None);
     }
 }
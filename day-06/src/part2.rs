@@ -1,12 +1,8 @@
-use itertools::Itertools;
+use crate::detector::first_marker;
 
-pub fn message_position(stream: &str) -> usize {
-    stream
-        .as_bytes()
-        .windows(14)
-        .position(|w| w.iter().unique().collect_vec().len() == 14)
-        .unwrap()
-        + 14
+/// Characters to read before the start-of-message marker, if any
+pub fn message_position(stream: &str) -> Option<usize> {
+    first_marker(stream.as_bytes(), 14).unwrap()
 }
 
 #[cfg(test)]
@@ -15,10 +11,16 @@ pub mod tests {
 
     #[test]
     fn test_example_message() {
-        assert_eq!(message_position("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), 19);
-        assert_eq!(message_position("bvwbjplbgvbhsrlpgdmjqwftvncz"), 23);
-        assert_eq!(message_position("nppdvjthqldpwncqszvftbrmjlhg"), 23);
-        assert_eq!(message_position("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), 29);
-        assert_eq!(message_position("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), 26);
+        assert_eq!(message_position("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Some(19));
+        assert_eq!(message_position("bvwbjplbgvbhsrlpgdmjqwftvncz"), Some(23));
+        assert_eq!(message_position("nppdvjthqldpwncqszvftbrmjlhg"), Some(23));
+        assert_eq!(
+            message_position("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"),
+            Some(29)
+        );
+        assert_eq!(
+            message_position("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"),
+            Some(26)
+        );
     }
 }
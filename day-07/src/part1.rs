@@ -1,25 +1,52 @@
 use indextree::{Arena, NodeId};
-use itertools::Itertools;
+
+use crate::shell::{Shell, ShellError};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Line {
     ChangeDir(String),
     List,
+    MakeDir(String),
+    /// `touch name [size]`, the size is an extension to create files
+    Touch(String, Option<usize>),
+    /// `rm [-r] path`
+    Remove(String, bool),
     Output(String),
 }
 
 impl Line {
-    pub fn new(line: &str) -> Line {
+    pub fn new(line: &str) -> Result<Line, ShellError> {
         let mut parts = line.split_whitespace();
         if parts.next() != Some("$") {
-            return Line::Output(line.to_string());
+            return Ok(Line::Output(line.to_string()));
         }
 
-        match parts.next() {
-            Some("cd") => Line::ChangeDir(parts.next().unwrap().to_string()),
-            Some("ls") => Line::List,
-            _ => panic!("Unknown command"),
-        }
+        let command = parts.next().unwrap_or_default();
+        let mut argument = |name| {
+            parts
+                .next()
+                .map(|a| a.to_string())
+                .ok_or(ShellError::MissingArgument(name))
+        };
+        let line = match command {
+            "cd" => Line::ChangeDir(argument("cd")?),
+            "ls" => Line::List,
+            "mkdir" => Line::MakeDir(argument("mkdir")?),
+            "touch" => {
+                let name = argument("touch")?;
+                let size = match argument("touch") {
+                    Ok(size) => Some(size.parse().map_err(|_| ShellError::InvalidSize(size))?),
+                    Err(_) => None,
+                };
+                Line::Touch(name, size)
+            }
+            "rm" => match argument("rm")?.as_str() {
+                "-r" => Line::Remove(argument("rm")?, true),
+                path => Line::Remove(path.to_string(), false),
+            },
+            command => return Err(ShellError::UnknownCommand(command.to_string())),
+        };
+        Ok(line)
     }
 }
 
@@ -30,13 +57,23 @@ pub enum FileSystemNode {
 }
 
 impl FileSystemNode {
+    pub fn name(&self) -> &str {
+        match self {
+            FileSystemNode::Dir(name, _) | FileSystemNode::File(name, _) => name,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self, FileSystemNode::Dir(..))
+    }
+
     pub fn is_dir_named(&self, name: &str) -> bool {
         matches!(self, FileSystemNode::Dir(n, _) if n == name)
     }
 
-    pub fn add_size(&mut self, size: usize) {
+    pub fn add_size(&mut self, size: isize) {
         match self {
-            FileSystemNode::Dir(_, s) => *s += size,
+            FileSystemNode::Dir(_, s) => *s = s.checked_add_signed(size).unwrap(),
             FileSystemNode::File(_, _) => panic!("Cannot add size to file"),
         }
     }
@@ -50,67 +87,16 @@ impl FileSystemNode {
 }
 
 pub fn parse_tree(data: &str) -> (Arena<FileSystemNode>, NodeId) {
-    let lines = data.lines().map(Line::new);
-    let mut tree: Arena<FileSystemNode> = Arena::new();
-    let root_id = tree.new_node(FileSystemNode::Dir("/".to_string(), 0));
-    let mut current_id = root_id;
-
-    for line in lines {
-        match line {
-            Line::ChangeDir(dir) if dir == "/" => {
-                current_id = root_id;
-            }
-            Line::ChangeDir(dir) if dir == ".." => {
-                current_id = tree.get(current_id).unwrap().parent().unwrap();
-            }
-            Line::ChangeDir(dir) => {
-                let child = current_id
-                    .children(&tree)
-                    .find(|&n| tree.get(n).unwrap().get().is_dir_named(&dir));
-
-                match child {
-                    Some(child_id) => current_id = child_id,
-                    None => {
-                        let new_id = tree.new_node(FileSystemNode::Dir(dir, 0));
-                        current_id.append(new_id, &mut tree);
-                        current_id = new_id;
-                    }
-                }
-            }
-            Line::List => {}
-            Line::Output(output) => {
-                let (size_or_dir, name) = output.split_once(' ').unwrap();
-                match size_or_dir {
-                    "dir" => {
-                        let new_id = tree.new_node(FileSystemNode::Dir(name.to_string(), 0));
-                        current_id.append(new_id, &mut tree);
-                    }
-                    size => {
-                        let size = size.parse::<usize>().unwrap();
-                        let new_id = tree.new_node(FileSystemNode::File(name.to_string(), size));
-                        current_id.append(new_id, &mut tree);
-
-                        let ancestors = current_id.ancestors(&tree).collect_vec();
-                        ancestors.iter().for_each(|&n| {
-                            tree.get_mut(n).unwrap().get_mut().add_size(size);
-                        });
-                    }
-                }
-            }
-        }
-    }
-
-    (tree, root_id)
+    let shell = Shell::parse(data).unwrap_or_else(|e| panic!("{}", e));
+    (shell.tree, shell.root)
 }
 
 #[allow(dead_code)]
-fn print_filesystem(tree: &Arena<FileSystemNode>) {
-    tree.iter().for_each(|n| {
-        let node = n.get();
-        let id = tree.get_node_id(n).unwrap();
+fn print_filesystem(tree: &Arena<FileSystemNode>, root: NodeId) {
+    root.descendants(tree).for_each(|id| {
         let depth = id.ancestors(tree).count();
         let indent = " ".repeat(depth * 2);
-        match node {
+        match tree.get(id).unwrap().get() {
             FileSystemNode::Dir(name, size) => println!("{}-{} ({})", indent, name, size),
             FileSystemNode::File(name, size) => println!("{} {} ({})", indent, name, size),
         }
@@ -118,11 +104,12 @@ fn print_filesystem(tree: &Arena<FileSystemNode>) {
 }
 
 pub fn find_directories_by_total_size(input: &str) -> usize {
-    let (tree, _root_id) = parse_tree(input);
-    // print_filesystem(&tree);
-    tree.iter()
-        .filter(|n| n.get().get_dir_size() < 100000)
-        .map(|n| n.get().get_dir_size())
+    let shell = Shell::parse(input).unwrap_or_else(|e| panic!("{}", e));
+    shell
+        .du(None)
+        .into_iter()
+        .map(|(_, size)| size)
+        .filter(|&size| size < 100000)
         .sum()
 }
 
@@ -132,9 +119,22 @@ pub mod tests {
 
     #[test]
     fn test_parse() {
-        assert_eq!(Line::new("$ cd /"), Line::ChangeDir("/".to_string()));
-        assert_eq!(Line::new("$ ls"), Line::List);
-        assert_eq!(Line::new("ls"), Line::Output("ls".to_string()));
+        assert_eq!(Line::new("$ cd /"), Ok(Line::ChangeDir("/".to_string())));
+        assert_eq!(Line::new("$ ls"), Ok(Line::List));
+        assert_eq!(Line::new("ls"), Ok(Line::Output("ls".to_string())));
+        assert_eq!(
+            Line::new("$ rm -r a"),
+            Ok(Line::Remove("a".to_string(), true))
+        );
+        assert_eq!(
+            Line::new("$ touch a 12"),
+            Ok(Line::Touch("a".to_string(), Some(12)))
+        );
+        assert_eq!(
+            Line::new("$ cat a"),
+            Err(ShellError::UnknownCommand("cat".to_string()))
+        );
+        assert_eq!(Line::new("$ cd"), Err(ShellError::MissingArgument("cd")));
     }
 
     #[test]
@@ -0,0 +1,469 @@
+use std::{error::Error, fmt};
+
+use indextree::{Arena, NodeEdge, NodeId};
+use itertools::Itertools;
+
+use crate::part1::{FileSystemNode, Line};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ShellError {
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    InvalidSize(String),
+    NotFound(String),
+    NotADirectory(String),
+    IsADirectory(String),
+    AlreadyExists(String),
+    /// A line that isn't a command outside of an `ls` listing
+    UnexpectedOutput(String),
+    CannotRemoveRoot,
+    /// A name that can't be a single directory entry, like `..` or `a/b`
+    InvalidName(String),
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellError::UnknownCommand(c) => write!(f, "{}: command not found", c),
+            ShellError::MissingArgument(c) => write!(f, "{}: missing operand", c),
+            ShellError::InvalidSize(s) => write!(f, "invalid size: {}", s),
+            ShellError::NotFound(p) => write!(f, "{}: no such file or directory", p),
+            ShellError::NotADirectory(p) => write!(f, "{}: not a directory", p),
+            ShellError::IsADirectory(p) => write!(f, "{}: is a directory", p),
+            ShellError::AlreadyExists(p) => write!(f, "{}: file exists", p),
+            ShellError::UnexpectedOutput(l) => write!(f, "unexpected output: {}", l),
+            ShellError::CannotRemoveRoot => write!(f, "refusing to remove /"),
+            ShellError::InvalidName(n) => write!(f, "{}: invalid name", n),
+        }
+    }
+}
+
+impl Error for ShellError {}
+
+/**
+ * Replays a terminal session over a filesystem tree. Directories keep the
+ * total size of their content, updated as files are added, resized or
+ * removed. Listing the same directory again doesn't duplicate entries.
+ */
+#[derive(Debug)]
+pub struct Shell {
+    pub tree: Arena<FileSystemNode>,
+    pub root: NodeId,
+    pub cwd: NodeId,
+    /// Whether output lines are currently expected, after an `ls`
+    listing: bool,
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shell {
+    pub fn new() -> Self {
+        let mut tree = Arena::new();
+        let root = tree.new_node(FileSystemNode::Dir("/".to_string(), 0));
+        Self {
+            tree,
+            root,
+            cwd: root,
+            listing: false,
+        }
+    }
+
+    /// Runs every line of a session
+    pub fn parse(input: &str) -> Result<Self, ShellError> {
+        let mut shell = Self::new();
+        for line in input.lines().filter(|l| !l.trim().is_empty()) {
+            shell.run(Line::new(line)?)?;
+        }
+        Ok(shell)
+    }
+
+    fn node(&self, id: NodeId) -> &FileSystemNode {
+        self.tree.get(id).unwrap().get()
+    }
+
+    pub fn size(&self, id: NodeId) -> usize {
+        match self.node(id) {
+            FileSystemNode::Dir(_, size) | FileSystemNode::File(_, size) => *size,
+        }
+    }
+
+    fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        dir.children(&self.tree)
+            .find(|&n| self.node(n).name() == name)
+    }
+
+    /// Absolute path of a node
+    pub fn path(&self, id: NodeId) -> String {
+        let names = id
+            .ancestors(&self.tree)
+            .filter(|&n| n != self.root)
+            .map(|n| self.node(n).name())
+            .collect_vec();
+        format!("/{}", names.into_iter().rev().join("/"))
+    }
+
+    /// Finds a node from a path, absolute or relative to the current
+    /// directory, with `..` components
+    pub fn resolve(&self, path: &str) -> Result<NodeId, ShellError> {
+        let mut current = if path.starts_with('/') {
+            self.root
+        } else {
+            self.cwd
+        };
+        for part in path.split('/').filter(|p| !p.is_empty() && *p != ".") {
+            if !self.node(current).is_dir() {
+                return Err(ShellError::NotADirectory(path.to_string()));
+            }
+            current = match part {
+                ".." => self
+                    .tree
+                    .get(current)
+                    .unwrap()
+                    .parent()
+                    .unwrap_or(self.root),
+                name => self
+                    .child(current, name)
+                    .ok_or_else(|| ShellError::NotFound(path.to_string()))?,
+            };
+        }
+        Ok(current)
+    }
+
+    /// Adds `delta` to the size of every directory containing `id`
+    fn propagate_size(&mut self, id: NodeId, delta: isize) {
+        let ancestors = id.ancestors(&self.tree).skip(1).collect_vec();
+        for n in ancestors {
+            self.tree.get_mut(n).unwrap().get_mut().add_size(delta);
+        }
+    }
+
+    /// Splits a path in the directory that should contain its last
+    /// component, which must exist, and the name of that component
+    fn split_path<'a>(&self, path: &'a str) -> Result<(NodeId, &'a str), ShellError> {
+        let trimmed = path.trim_end_matches('/');
+        let (parent, name) = match trimmed.rsplit_once('/') {
+            Some(("", name)) => (self.root, name),
+            Some((parent, name)) => (self.resolve(parent)?, name),
+            None => (self.cwd, trimmed),
+        };
+        if !self.node(parent).is_dir() {
+            return Err(ShellError::NotADirectory(path.to_string()));
+        }
+        Ok((parent, Self::entry_name(name)?))
+    }
+
+    fn entry_name(name: &str) -> Result<&str, ShellError> {
+        match name {
+            "" | "." | ".." => Err(ShellError::InvalidName(name.to_string())),
+            name if name.contains('/') => Err(ShellError::InvalidName(name.to_string())),
+            name => Ok(name),
+        }
+    }
+
+    /// Creates a directory, or returns the existing one
+    fn add_dir(&mut self, parent: NodeId, name: &str) -> Result<NodeId, ShellError> {
+        if let Some(existing) = self.child(parent, name) {
+            if !self.node(existing).is_dir() {
+                return Err(ShellError::NotADirectory(name.to_string()));
+            }
+            return Ok(existing);
+        }
+        let id = self.tree.new_node(FileSystemNode::Dir(name.to_string(), 0));
+        parent.append(id, &mut self.tree);
+        Ok(id)
+    }
+
+    /// Creates a file, or sets the size of an existing one
+    fn add_file(&mut self, parent: NodeId, name: &str, size: usize) -> Result<NodeId, ShellError> {
+        let id = match self.child(parent, name) {
+            Some(id) => id,
+            None => {
+                let id = self
+                    .tree
+                    .new_node(FileSystemNode::File(name.to_string(), 0));
+                parent.append(id, &mut self.tree);
+                id
+            }
+        };
+        let delta = size as isize - self.size(id) as isize;
+        match self.tree.get_mut(id).unwrap().get_mut() {
+            FileSystemNode::File(_, s) => *s = size,
+            FileSystemNode::Dir(..) => return Err(ShellError::IsADirectory(name.to_string())),
+        }
+        self.propagate_size(id, delta);
+        Ok(id)
+    }
+
+    pub fn run(&mut self, line: Line) -> Result<(), ShellError> {
+        if !matches!(line, Line::Output(_)) {
+            self.listing = false;
+        }
+
+        match line {
+            Line::ChangeDir(path) => {
+                let id = self.resolve(&path)?;
+                if !self.node(id).is_dir() {
+                    return Err(ShellError::NotADirectory(path));
+                }
+                self.cwd = id;
+            }
+            Line::List => self.listing = true,
+            Line::MakeDir(path) => {
+                let (parent, name) = self.split_path(&path)?;
+                if self.child(parent, name).is_some() {
+                    return Err(ShellError::AlreadyExists(path));
+                }
+                self.add_dir(parent, name)?;
+            }
+            Line::Touch(path, size) => {
+                let (parent, name) = self.split_path(&path)?;
+                match (self.child(parent, name), size) {
+                    (Some(_), None) => {}
+                    (_, size) => {
+                        self.add_file(parent, name, size.unwrap_or(0))?;
+                    }
+                }
+            }
+            Line::Remove(path, recursive) => {
+                let id = self.resolve(&path)?;
+                if id == self.root {
+                    return Err(ShellError::CannotRemoveRoot);
+                }
+                if self.node(id).is_dir() && !recursive {
+                    return Err(ShellError::IsADirectory(path));
+                }
+                if self.cwd.ancestors(&self.tree).contains(&id) {
+                    self.cwd = self.tree.get(id).unwrap().parent().unwrap();
+                }
+                self.propagate_size(id, -(self.size(id) as isize));
+                id.remove_subtree(&mut self.tree);
+            }
+            Line::Output(output) => {
+                if !self.listing {
+                    return Err(ShellError::UnexpectedOutput(output));
+                }
+                let (size_or_dir, name) = output
+                    .split_once(' ')
+                    .ok_or_else(|| ShellError::UnexpectedOutput(output.clone()))?;
+                let name = Self::entry_name(name)?;
+                match size_or_dir {
+                    "dir" => {
+                        self.add_dir(self.cwd, name)?;
+                    }
+                    size => {
+                        let size = size
+                            .parse::<usize>()
+                            .map_err(|_| ShellError::InvalidSize(size.to_string()))?;
+                        self.add_file(self.cwd, name, size)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Directories in `du` order (content before the directory itself) with
+    /// their path and total size, down to `max_depth` levels below the root
+    /// when given, as `du -d N`.
+    pub fn du(&self, max_depth: Option<usize>) -> Vec<(String, usize)> {
+        self.root
+            .traverse(&self.tree)
+            .filter_map(|edge| match edge {
+                NodeEdge::End(id) => Some(id),
+                NodeEdge::Start(_) => None,
+            })
+            .filter(|&id| self.node(id).is_dir())
+            .filter(|&id| max_depth.is_none_or(|d| id.ancestors(&self.tree).count() <= d + 1))
+            .map(|id| (self.path(id), self.size(id)))
+            .collect()
+    }
+
+    /// Files strictly larger than `size`, as `find -type f -size +N`
+    pub fn find_larger(&self, size: usize) -> Vec<(String, usize)> {
+        self.root
+            .descendants(&self.tree)
+            .filter(|&id| !self.node(id).is_dir() && self.size(id) > size)
+            .map(|id| (self.path(id), self.size(id)))
+            .collect()
+    }
+
+    /// Smallest directory that, once deleted, leaves at least `needed` free
+    /// space on a disk of size `total`
+    pub fn smallest_to_free(&self, total: usize, needed: usize) -> Option<(String, usize)> {
+        let free = total.saturating_sub(self.size(self.root));
+        let missing = needed.saturating_sub(free);
+        self.du(None)
+            .into_iter()
+            .filter(|(_, size)| *size >= missing)
+            .min_by_key(|(_, size)| *size)
+    }
+
+    fn write_json(&self, id: NodeId, json: &mut String) {
+        let node = self.node(id);
+        json.push_str(&format!(
+            r#"{{"name":{},"type":"{}","size":{}"#,
+            json_string(node.name()),
+            if node.is_dir() { "dir" } else { "file" },
+            self.size(id)
+        ));
+        if node.is_dir() {
+            json.push_str(r#","children":["#);
+            for (i, child) in id.children(&self.tree).enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                self.write_json(child, json);
+            }
+            json.push(']');
+        }
+        json.push('}');
+    }
+
+    /// The whole tree as JSON, directories with a `children` array
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(self.root, &mut json);
+        json
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::shell::*;
+
+    #[test]
+    fn test_repeated_listing() {
+        let input = include_str!("../test.txt");
+        let once = Shell::parse(input).unwrap();
+        let twice = Shell::parse(&format!("{}\n$ cd /\n{}", input, input)).unwrap();
+        assert_eq!(once.size(once.root), 48381165);
+        assert_eq!(twice.size(twice.root), 48381165);
+        assert_eq!(twice.root.descendants(&twice.tree).count(), 14);
+    }
+
+    #[test]
+    fn test_conflicting_listing() {
+        assert_eq!(
+            Shell::parse("$ ls\n12 a\ndir a").unwrap_err(),
+            ShellError::NotADirectory("a".to_string())
+        );
+        assert_eq!(
+            Shell::parse("$ ls\ndir a\n12 a").unwrap_err(),
+            ShellError::IsADirectory("a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut shell = Shell::parse(include_str!("../test.txt")).unwrap();
+        shell.run(Line::new("$ cd /a/e").unwrap()).unwrap();
+        assert_eq!(shell.path(shell.cwd), "/a/e");
+
+        shell.run(Line::new("$ mkdir logs").unwrap()).unwrap();
+        shell.run(Line::new("$ cd logs").unwrap()).unwrap();
+        shell
+            .run(Line::new("$ touch today.log 1000").unwrap())
+            .unwrap();
+        assert_eq!(shell.size(shell.resolve("/a").unwrap()), 94853 + 1000);
+
+        assert_eq!(
+            shell.run(Line::new("$ rm /a").unwrap()),
+            Err(ShellError::IsADirectory("/a".to_string()))
+        );
+        shell.run(Line::new("$ rm -r /a/e").unwrap()).unwrap();
+        assert_eq!(shell.path(shell.cwd), "/a");
+        assert_eq!(shell.size(shell.resolve("/a").unwrap()), 94853 - 584);
+        assert_eq!(
+            shell.run(Line::new("$ cd e").unwrap()),
+            Err(ShellError::NotFound("e".to_string()))
+        );
+        assert_eq!(
+            shell.run(Line::new("1234 stray").unwrap()),
+            Err(ShellError::UnexpectedOutput("1234 stray".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let mut shell = Shell::parse(include_str!("../test.txt")).unwrap();
+        let mut run = |line: &str| shell.run(Line::new(line).unwrap());
+        run("$ cd /").unwrap();
+        run("$ mkdir /a/logs/").unwrap();
+        run("$ mkdir a/logs/old").unwrap();
+        run("$ touch /a/logs/old/x.log 10").unwrap();
+        assert_eq!(
+            run("$ mkdir a/missing/dir"),
+            Err(ShellError::NotFound("a/missing".to_string()))
+        );
+        assert_eq!(
+            run("$ mkdir b.txt/dir"),
+            Err(ShellError::NotADirectory("b.txt/dir".to_string()))
+        );
+        assert_eq!(
+            run("$ mkdir a/.."),
+            Err(ShellError::InvalidName("..".to_string()))
+        );
+        assert_eq!(
+            run("$ mkdir /a/logs"),
+            Err(ShellError::AlreadyExists("/a/logs".to_string()))
+        );
+
+        let old = shell.resolve("/a/logs/old").unwrap();
+        assert_eq!(shell.path(old), "/a/logs/old");
+        assert_eq!(shell.node(old).name(), "old");
+        assert_eq!(shell.size(shell.resolve("/a").unwrap()), 94853 + 10);
+    }
+
+    #[test]
+    fn test_queries() {
+        let shell = Shell::parse(include_str!("../test.txt")).unwrap();
+        assert_eq!(
+            shell.du(Some(1)),
+            vec![
+                ("/a".to_string(), 94853),
+                ("/d".to_string(), 24933642),
+                ("/".to_string(), 48381165)
+            ]
+        );
+        assert_eq!(shell.du(None)[0], ("/a/e".to_string(), 584));
+        assert_eq!(
+            shell.find_larger(8_000_000),
+            vec![
+                ("/b.txt".to_string(), 14848514),
+                ("/c.dat".to_string(), 8504156),
+                ("/d/d.log".to_string(), 8033020)
+            ]
+        );
+        assert_eq!(
+            shell.smallest_to_free(70_000_000, 30_000_000),
+            Some(("/d".to_string(), 24933642))
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let shell = Shell::parse("$ ls\ndir a\n12 \"x\".txt").unwrap();
+        assert_eq!(
+            shell.to_json(),
+            r#"{"name":"/","type":"dir","size":12,"children":[{"name":"a","type":"dir","size":0,"children":[]},{"name":"\"x\".txt","type":"file","size":12}]}"#
+        );
+    }
+}
@@ -1,12 +1,13 @@
 use colored::Colorize;
 use grid::Grid;
 
+use crate::visibility::{Visibility, ORTHOGONAL};
+
 pub fn parse_grid(data: &str) -> Grid<usize> {
     let lines: Vec<Vec<usize>> = data
         .lines()
         .map(|l| {
             l.chars()
-                .into_iter()
                 .map(|c| c.to_digit(10).unwrap() as usize)
                 .collect()
         })
@@ -36,18 +37,7 @@ pub fn is_invisible(pos: Pos, grid: &Grid<usize>) -> bool {
 
 pub fn count_visible_trees(data: &str) -> usize {
     let grid = parse_grid(data);
-    let mut count = (grid.cols() + grid.rows() - 2) * 2;
-    println!("rows {} cols {}", grid.rows(), grid.cols());
-    for y in 1..grid.rows() - 1 {
-        for x in 1..grid.cols() - 1 {
-            if !is_invisible(Pos { x, y }, &grid) {
-                // println!("----- {} (={}) visible at {},{}", grid_get_at(&grid, Pos { x, y }), *grid.get(y, x).unwrap(),  x+1, y+1);
-                // print_grid_highlight(&grid, Pos { x, y });
-                count += 1;
-            }
-        }
-    }
-    count
+    Visibility::new(&grid, ORTHOGONAL).visible_count()
 }
 
 #[allow(dead_code)]
@@ -1,15 +1,17 @@
 use grid::Grid;
 
+use crate::visibility::{Visibility, ORTHOGONAL};
+
 #[derive(Debug, Copy, Clone)]
 pub struct Pos {
-    x: isize,
-    y: isize,
+    pub x: isize,
+    pub y: isize,
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct Direction {
-    dx: isize,
-    dy: isize,
+    pub dx: isize,
+    pub dy: isize,
 }
 
 impl Pos {
@@ -51,15 +53,8 @@ pub fn count_trees_in_direction(grid: &Grid<usize>, pos: Pos, dir: Direction) ->
     count
 }
 
-const DIRS: &[Direction] = &[
-    Direction { dx: 0, dy: -1 },
-    Direction { dx: 0, dy: 1 },
-    Direction { dx: -1, dy: 0 },
-    Direction { dx: 1, dy: 0 },
-];
-
 pub fn tree_scenic_score(grid: &Grid<usize>, pos: Pos) -> usize {
-    let score = DIRS
+    let score = ORTHOGONAL
         .iter()
         .map(|dir| count_trees_in_direction(grid, pos, *dir))
         .product();
@@ -67,22 +62,7 @@ pub fn tree_scenic_score(grid: &Grid<usize>, pos: Pos) -> usize {
 }
 
 pub fn max_scenic_score(grid: &Grid<usize>) -> usize {
-    let mut max = 0;
-    for y in 0..grid.rows() {
-        for x in 0..grid.cols() {
-            let score = tree_scenic_score(
-                grid,
-                Pos {
-                    x: x as isize,
-                    y: y as isize,
-                },
-            );
-            if score > max {
-                max = score;
-            }
-        }
-    }
-    max
+    Visibility::new(grid, ORTHOGONAL).max_scenic_score()
 }
 
 #[cfg(test)]
@@ -103,5 +83,6 @@ pub mod tests {
         );
         assert_eq!(tree_scenic_score(&grid, Pos { x: 2, y: 1 }), 4);
         assert_eq!(tree_scenic_score(&grid, Pos { x: 2, y: 3 }), 8);
+        assert_eq!(max_scenic_score(&grid), 8);
     }
 }
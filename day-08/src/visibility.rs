@@ -0,0 +1,230 @@
+use grid::Grid;
+use toolkit::animation::{Frame, Rgb};
+
+use crate::part2::Direction;
+
+pub const ORTHOGONAL: &[Direction] = &[
+    Direction { dx: 0, dy: -1 },
+    Direction { dx: 0, dy: 1 },
+    Direction { dx: -1, dy: 0 },
+    Direction { dx: 1, dy: 0 },
+];
+
+pub const DIAGONAL: &[Direction] = &[
+    Direction { dx: -1, dy: -1 },
+    Direction { dx: 1, dy: -1 },
+    Direction { dx: -1, dy: 1 },
+    Direction { dx: 1, dy: 1 },
+];
+
+/// What a tree sees looking in a single direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct View {
+    /// Trees seen, up to the first one at least as tall or the edge
+    pub distance: usize,
+    /// Whether all the trees up to the edge are shorter
+    pub visible: bool,
+}
+
+/**
+ * Views of every tree in a set of directions. Each direction splits the
+ * grid in lines of trees, walked from the edge the trees look at: a
+ * monotonic stack keeps the trees already walked that aren't hidden by a
+ * taller (or equal) one, so its top after popping the shorter ones is the
+ * tree blocking the view. That's O(1) amortized per tree and direction.
+ */
+#[derive(Debug, Clone)]
+pub struct Visibility {
+    pub rows: usize,
+    pub cols: usize,
+    pub directions: Vec<Direction>,
+    /// Views indexed by direction, then `y * cols + x`
+    views: Vec<Vec<View>>,
+}
+
+impl Visibility {
+    pub fn new(grid: &Grid<usize>, directions: &[Direction]) -> Self {
+        let (rows, cols) = (grid.rows(), grid.cols());
+        let views = directions
+            .iter()
+            .map(|dir| {
+                assert!(dir.dx != 0 || dir.dy != 0, "Direction can't be zero");
+                Self::views_towards(grid, *dir)
+            })
+            .collect();
+
+        Self {
+            rows,
+            cols,
+            directions: directions.to_vec(),
+            views,
+        }
+    }
+
+    fn inside(grid: &Grid<usize>, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < grid.cols() && (y as usize) < grid.rows()
+    }
+
+    fn views_towards(grid: &Grid<usize>, dir: Direction) -> Vec<View> {
+        let mut views = vec![View::default(); grid.rows() * grid.cols()];
+        let mut line = vec![];
+        let mut stack: Vec<(usize, usize)> = vec![];
+
+        for ((y, x), _) in grid.indexed_iter() {
+            let (x, y) = (x as isize, y as isize);
+            // Every line starts from the tree with nothing behind it
+            if Self::inside(grid, x - dir.dx, y - dir.dy) {
+                continue;
+            }
+
+            line.clear();
+            let (mut lx, mut ly) = (x, y);
+            while Self::inside(grid, lx, ly) {
+                line.push((lx as usize, ly as usize));
+                lx += dir.dx;
+                ly += dir.dy;
+            }
+
+            stack.clear();
+            for (i, &(tx, ty)) in line.iter().enumerate().rev() {
+                let height = *grid.get(ty, tx).unwrap();
+                while matches!(stack.last(), Some(&(_, h)) if h < height) {
+                    stack.pop();
+                }
+                views[ty * grid.cols() + tx] = match stack.last() {
+                    Some(&(blocker, _)) => View {
+                        distance: blocker - i,
+                        visible: false,
+                    },
+                    None => View {
+                        distance: line.len() - 1 - i,
+                        visible: true,
+                    },
+                };
+                stack.push((i, height));
+            }
+        }
+        views
+    }
+
+    /// View of the tree at `(x, y)` towards the `i`-th direction
+    pub fn view(&self, direction: usize, x: usize, y: usize) -> View {
+        self.views[direction][y * self.cols + x]
+    }
+
+    /// Whether the tree is visible from outside the grid in any direction
+    pub fn is_visible(&self, x: usize, y: usize) -> bool {
+        (0..self.directions.len()).any(|d| self.view(d, x, y).visible)
+    }
+
+    pub fn visible_count(&self) -> usize {
+        (0..self.rows)
+            .flat_map(|y| (0..self.cols).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_visible(x, y))
+            .count()
+    }
+
+    /// Product of the view distances in every direction
+    pub fn scenic_score(&self, x: usize, y: usize) -> usize {
+        (0..self.directions.len())
+            .map(|d| self.view(d, x, y).distance)
+            .product()
+    }
+
+    pub fn scenic_scores(&self) -> Grid<usize> {
+        let scores = (0..self.rows)
+            .flat_map(|y| (0..self.cols).map(move |x| (x, y)))
+            .map(|(x, y)| self.scenic_score(x, y))
+            .collect();
+        Grid::from_vec(scores, self.cols)
+    }
+
+    pub fn max_scenic_score(&self) -> usize {
+        self.scenic_scores().iter().copied().max().unwrap_or(0)
+    }
+
+    /// Scenic scores as an image, from black (zero) through red to yellow
+    /// (the best score), on a square root scale so that the few very high
+    /// scores don't flatten the others.
+    pub fn heatmap(&self, scale: usize) -> Frame {
+        let scores = self.scenic_scores();
+        let max = (self.max_scenic_score() as f64).sqrt().max(1.0);
+        let color = |score: usize| -> Rgb {
+            let t = (score as f64).sqrt() / max;
+            let red = (t * 2.0).min(1.0);
+            let green = (t * 2.0 - 1.0).max(0.0);
+            [
+                (red * 255.0).round() as u8,
+                (green * 255.0).round() as u8,
+                0,
+            ]
+        };
+
+        let (width, height) = (self.cols * scale, self.rows * scale);
+        let pixels = (0..height)
+            .flat_map(|py| (0..width).map(move |px| (px / scale, py / scale)))
+            .map(|(x, y)| color(*scores.get(y, x).unwrap()))
+            .collect();
+        Frame {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        part1::{is_invisible, parse_grid},
+        part2::{count_trees_in_direction, Pos},
+        visibility::*,
+    };
+
+    #[test]
+    fn test_example() {
+        let grid = parse_grid(include_str!("../test.txt"));
+        let visibility = Visibility::new(&grid, ORTHOGONAL);
+        assert_eq!(visibility.visible_count(), 21);
+        assert_eq!(visibility.max_scenic_score(), 8);
+        assert_eq!(visibility.scenic_score(2, 3), 8);
+    }
+
+    #[test]
+    fn test_matches_scanning() {
+        let grid = parse_grid(include_str!("../test.txt"));
+        let directions = [ORTHOGONAL, DIAGONAL, &[Direction { dx: 1, dy: 2 }]].concat();
+        let visibility = Visibility::new(&grid, &directions);
+        let orthogonal = Visibility::new(&grid, ORTHOGONAL);
+
+        for (y, x) in grid.indexed_iter().map(|(p, _)| p) {
+            for (d, dir) in directions.iter().enumerate() {
+                let pos = Pos {
+                    x: x as isize,
+                    y: y as isize,
+                };
+                assert_eq!(
+                    visibility.view(d, x, y).distance,
+                    count_trees_in_direction(&grid, pos, *dir)
+                );
+            }
+            let inner = x > 0 && y > 0 && x < grid.cols() - 1 && y < grid.rows() - 1;
+            if inner {
+                assert_eq!(
+                    orthogonal.is_visible(x, y),
+                    !is_invisible(crate::part1::Pos { x, y }, &grid)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let grid = parse_grid(include_str!("../test.txt"));
+        let frame = Visibility::new(&grid, ORTHOGONAL).heatmap(2);
+        assert_eq!((frame.width, frame.height), (10, 10));
+        // The best tree is at (2, 3), edges have a zero score
+        assert_eq!(frame.pixels[(3 * 2) * 10 + 2 * 2], [255, 255, 0]);
+        assert_eq!(frame.pixels[0], [0, 0, 0]);
+    }
+}
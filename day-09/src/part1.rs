@@ -1,114 +1,67 @@
-use std::{cmp::max, collections::HashSet};
-
-use num::abs;
-
-pub fn part1() {}
+use crate::rope::{Rope, RopeError};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Pos(pub i32, pub i32);
 
-pub struct Grid {
-    pub map: HashSet<Pos>,
-    head: Pos,
-    tail: Pos,
-}
-
-impl Grid {
-    fn is_tail_adjacent(&self) -> bool {
-        let Pos(x, y) = self.tail;
-        let Pos(hx, hy) = self.head;
-        abs(x - hx) <= 1 && abs(y - hy) <= 1
-    }
-
-    #[allow(dead_code)]
-    fn print(&self) {
-        let mut values = self
-            .map
-            .iter()
-            .map(|Pos(x, y)| (*x, *y))
-            .collect::<Vec<_>>();
-
-        values.push((self.head.0, self.head.1));
-
-        let min_x = *values.iter().map(|(x, _)| x).min().unwrap() - 2;
-        let max_x = *values.iter().map(|(x, _)| x).max().unwrap() + 2;
-        let min_y = *values.iter().map(|(_, y)| y).min().unwrap() - 2;
-        let max_y = *values.iter().map(|(_, y)| y).max().unwrap() + 2;
-
-        for y in min_y..max_y + 1 {
-            for x in min_x..max_x + 1 {
-                match (x, y) {
-                    (x, y) if self.head == Pos(x, y) => print!("H"),
-                    (x, y) if self.tail == Pos(x, y) => print!("T"),
-                    _ => print!("."),
-                }
-            }
-            println!()
-        }
-    }
-}
-
+/**
+ * A move like `R 4`. Directions can be diagonal (`UL`, `UR`, `DL`, `DR`)
+ * and an optional third field picks the knot to move, as in `U 2 3`,
+ * defaulting to the head.
+ */
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Step {
     pub heading: (i32, i32),
     pub dist: usize,
+    pub knot: usize,
 }
 
 impl Step {
     pub fn new(data: &str) -> Self {
-        let (dir, dist) = data.split_once(' ').unwrap();
-        let dist = dist.parse::<usize>().unwrap();
+        let mut parts = data.split_whitespace();
+        let dir = parts.next().expect("Missing direction");
+        let dist = parts
+            .next()
+            .expect("Missing distance")
+            .parse::<usize>()
+            .unwrap();
+        let knot = parts
+            .next()
+            .map(|k| k.parse::<usize>().unwrap())
+            .unwrap_or(0);
         let heading = match dir {
             "U" => (0, -1),
             "D" => (0, 1),
             "L" => (-1, 0),
             "R" => (1, 0),
+            "UL" => (-1, -1),
+            "UR" => (1, -1),
+            "DL" => (-1, 1),
+            "DR" => (1, 1),
             _ => panic!("Invalid direction"),
         };
-        Self { heading, dist }
-    }
-}
-
-fn simulate_tail(grid: &mut Grid) {
-    if grid.is_tail_adjacent() {
-        return;
+        Self {
+            heading,
+            dist,
+            knot,
+        }
     }
-
-    let Pos(x, y) = grid.tail;
-    let Pos(hx, hy) = grid.head;
-
-    let dx = (hx - x) / max((hx - x).abs(), 1);
-    let dy = (hy - y) / max((hy - y).abs(), 1);
-    // println!("dx: {}, dy: {}", dx, dy);
-
-    grid.tail = Pos(x + dx, y + dy);
-    grid.map.insert(grid.tail);
 }
 
-fn run_step(grid: &mut Grid, step: &Step) {
-    let (dx, dy) = step.heading;
-    for _i in 0..step.dist {
-        // println!("Step {}", i);
-        grid.head = Pos(grid.head.0 + dx, grid.head.1 + dy);
-        simulate_tail(grid);
-
-        // grid.print();
+/// Runs the steps on a rope with `knots` knots, failing on a step that
+/// moves a knot the rope doesn't have
+pub fn run_rope(input: &str, knots: usize) -> Result<Rope, RopeError> {
+    let mut rope = Rope::new(knots);
+    for step in input.lines().map(Step::new) {
+        rope.apply(&step)?;
     }
+    Ok(rope)
 }
 
 pub fn simulate_ropes(input: &str) -> usize {
-    let mut grid = Grid {
-        map: HashSet::new(),
-        head: Pos(0, 0),
-        tail: Pos(0, 0),
-    };
-    grid.map.insert(grid.tail);
-
-    input
-        .lines()
-        .map(Step::new)
-        .for_each(|step| run_step(&mut grid, &step));
-
-    grid.map.len()
+    run_rope(input, 2)
+        .unwrap_or_else(|e| panic!("{}", e))
+        .visited(1)
+        .len()
 }
 
 #[cfg(test)]
@@ -120,4 +73,17 @@ pub mod tests {
         let input = include_str!("../test.txt");
         assert_eq!(simulate_ropes(input), 13);
     }
+
+    #[test]
+    fn test_parse_step() {
+        assert_eq!(
+            Step::new("DL 3 2"),
+            Step {
+                heading: (-1, 1),
+                dist: 3,
+                knot: 2
+            }
+        );
+        assert_eq!(Step::new("R 4").knot, 0);
+    }
 }
@@ -0,0 +1,269 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt::{self, Write},
+};
+
+use itertools::Itertools;
+use toolkit::{
+    animation::{Frame, Palette, Rgb},
+    map::{self, Map},
+};
+
+use crate::part1::{Pos, Step};
+
+const TRAIL: Rgb = [255, 255, 255];
+const START: Rgb = [0, 200, 0];
+const END: Rgb = [220, 0, 0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RopeError {
+    /// A step moving a knot past the end of the rope
+    MissingKnot { knot: usize, len: usize },
+}
+
+impl fmt::Display for RopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RopeError::MissingKnot { knot, len } => {
+                write!(f, "no knot {} in a rope of {} knots", knot, len)
+            }
+        }
+    }
+}
+
+impl Error for RopeError {}
+
+/**
+ * A rope made of any number of knots, starting all at the origin. Every
+ * knot keeps its trail (each position it moved to, in order) and the set
+ * of positions it visited.
+ */
+#[derive(Debug, Clone)]
+pub struct Rope {
+    pub knots: Vec<Pos>,
+    trails: Vec<Vec<Pos>>,
+    visited: Vec<HashSet<Pos>>,
+}
+
+impl Rope {
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "A rope needs at least one knot");
+        let origin = Pos(0, 0);
+        Self {
+            knots: vec![origin; len],
+            trails: vec![vec![origin]; len],
+            visited: vec![HashSet::from([origin]); len],
+        }
+    }
+
+    pub fn head(&self) -> Pos {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> Pos {
+        *self.knots.last().unwrap()
+    }
+
+    /// Positions visited by `knot`
+    pub fn visited(&self, knot: usize) -> &HashSet<Pos> {
+        &self.visited[knot]
+    }
+
+    /// Positions `knot` moved to, in order, starting from the origin
+    pub fn trail(&self, knot: usize) -> &[Pos] {
+        &self.trails[knot]
+    }
+
+    fn set(&mut self, knot: usize, pos: Pos) {
+        self.knots[knot] = pos;
+        self.trails[knot].push(pos);
+        self.visited[knot].insert(pos);
+    }
+
+    /// Moves `knot` by one towards `leader` unless they're touching,
+    /// returning whether it moved.
+    fn follow(&mut self, knot: usize, leader: usize) -> bool {
+        let Pos(x, y) = self.knots[knot];
+        let Pos(lx, ly) = self.knots[leader];
+        if (lx - x).abs() <= 1 && (ly - y).abs() <= 1 {
+            return false;
+        }
+        self.set(knot, Pos(x + (lx - x).signum(), y + (ly - y).signum()));
+        true
+    }
+
+    /// Lets the knots after and before `knot` follow it, each one stopping
+    /// the pull as soon as a knot doesn't need to move.
+    fn pull(&mut self, knot: usize) {
+        for i in knot + 1..self.knots.len() {
+            if !self.follow(i, i - 1) {
+                break;
+            }
+        }
+        for i in (0..knot).rev() {
+            if !self.follow(i, i + 1) {
+                break;
+            }
+        }
+    }
+
+    /// Moves `knot` by `(dx, dy)`, which can be diagonal or longer than a
+    /// single cell. The knot travels one cell at a time (diagonally first)
+    /// and the rest of the rope follows it at every cell.
+    pub fn move_knot(&mut self, knot: usize, dx: i32, dy: i32) -> Result<(), RopeError> {
+        if knot >= self.knots.len() {
+            return Err(RopeError::MissingKnot {
+                knot,
+                len: self.knots.len(),
+            });
+        }
+        let (mut dx, mut dy) = (dx, dy);
+        while dx != 0 || dy != 0 {
+            let (sx, sy) = (dx.signum(), dy.signum());
+            let Pos(x, y) = self.knots[knot];
+            self.set(knot, Pos(x + sx, y + sy));
+            self.pull(knot);
+            dx -= sx;
+            dy -= sy;
+        }
+        Ok(())
+    }
+
+    pub fn apply(&mut self, step: &Step) -> Result<(), RopeError> {
+        let (dx, dy) = step.heading;
+        for _ in 0..step.dist {
+            self.move_knot(step.knot, dx, dy)?;
+        }
+        Ok(())
+    }
+
+    fn trail_bounds(trail: &[Pos]) -> map::Bounds {
+        let (min_x, max_x) = trail.iter().map(|p| p.0).minmax().into_option().unwrap();
+        let (min_y, max_y) = trail.iter().map(|p| p.1).minmax().into_option().unwrap();
+        map::Bounds::new(map::Pos::new(min_x, min_y), map::Pos::new(max_x, max_y))
+    }
+
+    /// The trail of `knot` as an SVG polyline, with a cell per unit and the
+    /// start and end positions marked. `None` if there is no such knot.
+    pub fn trail_svg(&self, knot: usize) -> Option<String> {
+        let trail = self.trails.get(knot)?;
+        let bounds = Self::trail_bounds(trail);
+        let (first, last) = (trail[0], *trail.last().unwrap());
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            bounds.min.x - 1,
+            bounds.min.y - 1,
+            bounds.width() + 1,
+            bounds.height() + 1
+        )
+        .unwrap();
+        let path = trail
+            .iter()
+            .enumerate()
+            .map(|(i, Pos(x, y))| format!("{}{} {}", if i == 0 { 'M' } else { 'L' }, x, y))
+            .join(" ");
+        writeln!(
+            svg,
+            r#"  <path d="{}" fill="none" stroke="black" stroke-width="0.2"/>"#,
+            path
+        )
+        .unwrap();
+        for (Pos(x, y), color) in [(first, "green"), (last, "red")] {
+            writeln!(
+                svg,
+                r#"  <circle cx="{}" cy="{}" r="0.3" fill="{}"/>"#,
+                x, y, color
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>\n");
+        Some(svg)
+    }
+
+    /// The cells visited by `knot` as a binary PPM image, with the start in
+    /// green and the current position in red. `None` if there is no such knot.
+    pub fn trail_ppm(&self, knot: usize, scale: usize) -> Option<Vec<u8>> {
+        let trail = self.trails.get(knot)?;
+        let mut map = Map::new();
+        for Pos(x, y) in self.visited[knot].iter() {
+            map.tiles.insert(map::Pos::new(*x, *y), TRAIL);
+        }
+        for (Pos(x, y), color) in [(trail[0], START), (*trail.last().unwrap(), END)] {
+            map.tiles.insert(map::Pos::new(x, y), color);
+        }
+
+        let palette = Palette::new([0, 0, 0], |rgb: &Rgb| *rgb);
+        Some(Frame::from_map(&map, &palette, Self::trail_bounds(trail), scale).to_ppm())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{part1::run_rope, rope::*};
+
+    #[test]
+    fn test_visited_per_knot() {
+        let mut rope = Rope::new(10);
+        include_str!("../test2.txt")
+            .lines()
+            .map(Step::new)
+            .for_each(|step| rope.apply(&step).unwrap());
+        assert_eq!(rope.visited(9).len(), 36);
+        assert_eq!(rope.head(), Pos(-11, -15));
+        assert!(rope.visited(1).len() > rope.visited(9).len());
+    }
+
+    #[test]
+    fn test_diagonal_and_long_moves() {
+        let mut rope = Rope::new(3);
+        rope.move_knot(0, 3, 3).unwrap();
+        assert_eq!(rope.knots, vec![Pos(3, 3), Pos(2, 2), Pos(1, 1)]);
+
+        // Pulling the tail drags the other knots behind it
+        rope.move_knot(2, -3, 0).unwrap();
+        assert_eq!(rope.knots, vec![Pos(0, 1), Pos(-1, 1), Pos(-2, 1)]);
+        assert_eq!(
+            rope.trail(2),
+            &[Pos(0, 0), Pos(1, 1), Pos(0, 1), Pos(-1, 1), Pos(-2, 1)]
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let mut rope = Rope::new(2);
+        rope.apply(&Step::new("R 4")).unwrap();
+        rope.apply(&Step::new("U 2")).unwrap();
+
+        let svg = rope.trail_svg(1).unwrap();
+        assert!(svg.contains(r#"d="M0 0 L1 0 L2 0 L3 0 L4 -1""#));
+
+        // The tail visited (0..=4, 0) and (4, -1), in a 5x2 image
+        let ppm = rope.trail_ppm(1, 1).unwrap();
+        assert!(ppm.starts_with(b"P6\n5 2\n255\n"));
+        let pixels = &ppm[11..];
+        assert_eq!(&pixels[0..3], &[0, 0, 0]);
+        assert_eq!(&pixels[12..15], &END);
+        assert_eq!(&pixels[15..18], &START);
+
+        assert_eq!(rope.trail_svg(2), None);
+        assert_eq!(rope.trail_ppm(2, 1), None);
+    }
+
+    #[test]
+    fn test_missing_knot() {
+        let mut rope = Rope::new(10);
+        assert_eq!(
+            rope.apply(&Step::new("U 2 12")),
+            Err(RopeError::MissingKnot { knot: 12, len: 10 })
+        );
+        assert_eq!(rope.knots, vec![Pos(0, 0); 10]);
+        assert_eq!(
+            run_rope("R 1\nU 2 2", 2).unwrap_err().to_string(),
+            "no knot 2 in a rope of 2 knots"
+        );
+    }
+}
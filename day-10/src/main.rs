@@ -1,4 +1,7 @@
-use day_10::{part1::signal_strength, part2::render_crt};
+use day_10::{
+    part1::signal_strength,
+    part2::{read_crt, render_crt},
+};
 use runner::Runner;
 
 fn main() {
@@ -6,6 +9,11 @@ fn main() {
     let input = include_str!("../input.txt");
     println!("Signal strength: {}", signal_strength(input));
     println!("CRT Shows:");
-    render_crt(input);
+    println!("{}", render_crt(input));
+    let recognition = read_crt(input);
+    println!("CRT Letters: {}", recognition.text);
+    if !recognition.is_complete() {
+        println!("Unknown glyphs at {:?}", recognition.unknown);
+    }
     runner.end();
 }
@@ -0,0 +1,251 @@
+use itertools::Itertools;
+
+/**
+ * A bitmap font used by the elves' devices, where every glyph is drawn
+ * with `#` (lit) and `.` (dark) pixels.
+ */
+pub struct Font {
+    pub width: usize,
+    pub height: usize,
+    /// Horizontal distance between the start of two consecutive glyphs
+    pub pitch: usize,
+    pub glyphs: &'static [(char, &'static str)],
+}
+
+/**
+ * The result of reading a screen: unknown glyphs are reported as `?` in
+ * `text`, and their indices are listed in `unknown`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recognition {
+    pub text: String,
+    pub unknown: Vec<usize>,
+}
+
+impl Recognition {
+    pub fn is_complete(&self) -> bool {
+        self.unknown.is_empty()
+    }
+}
+
+impl Font {
+    /// The 4×6 font, letters are separated by a single dark column.
+    pub const SMALL: Font = Font {
+        width: 4,
+        height: 6,
+        pitch: 5,
+        glyphs: SMALL_GLYPHS,
+    };
+
+    /// The 6×10 font, letters are separated by two dark columns.
+    pub const LARGE: Font = Font {
+        width: 6,
+        height: 10,
+        pitch: 8,
+        glyphs: LARGE_GLYPHS,
+    };
+
+    /// Picks the font matching the height of the screen, if any.
+    pub fn for_height(height: usize) -> Option<&'static Font> {
+        [&Self::SMALL, &Self::LARGE]
+            .into_iter()
+            .find(|font| font.height == height)
+    }
+
+    /// Reads every glyph of the screen, from left to right.
+    pub fn recognize(&self, screen: &str) -> Recognition {
+        let rows = screen
+            .lines()
+            .map(|line| line.chars().map(|c| c == '#').collect_vec())
+            .filter(|row| !row.is_empty())
+            .collect_vec();
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let count = (columns + self.pitch - self.width) / self.pitch;
+
+        let mut text = String::new();
+        let mut unknown = vec![];
+        for index in 0..count {
+            let offset = index * self.pitch;
+            let cell = (0..self.height)
+                .map(|y| {
+                    (0..self.width)
+                        .map(|x| {
+                            let lit = rows
+                                .get(y)
+                                .and_then(|row| row.get(offset + x))
+                                .copied()
+                                .unwrap_or(false);
+                            if lit {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect::<String>()
+                })
+                .join("\n");
+
+            match self.glyphs.iter().find(|(_, glyph)| *glyph == cell) {
+                Some((letter, _)) => text.push(*letter),
+                None => {
+                    text.push('?');
+                    unknown.push(index);
+                }
+            }
+        }
+
+        Recognition { text, unknown }
+    }
+
+    /// Draws the text with this font, the inverse of `recognize`.
+    /// Panics if a letter is not part of the font.
+    pub fn render(&self, text: &str) -> String {
+        let glyphs = text
+            .chars()
+            .map(|letter| {
+                let (_, glyph) = self
+                    .glyphs
+                    .iter()
+                    .find(|(c, _)| *c == letter)
+                    .unwrap_or_else(|| panic!("Letter {} is not in the font", letter));
+                glyph.lines().collect_vec()
+            })
+            .collect_vec();
+        let spacing = ".".repeat(self.pitch - self.width);
+
+        (0..self.height)
+            .map(|y| glyphs.iter().map(|glyph| glyph[y]).join(&spacing))
+            .join("\n")
+    }
+}
+
+/// Reads the screen using the font matching its height.
+pub fn recognize(screen: &str) -> Option<Recognition> {
+    let height = screen.lines().filter(|line| !line.is_empty()).count();
+    Font::for_height(height).map(|font| font.recognize(screen))
+}
+
+const SMALL_GLYPHS: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const LARGE_GLYPHS: &[(char, &str)] = &[
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+#[cfg(test)]
+pub mod tests {
+    use crate::ocr::*;
+
+    #[test]
+    fn test_small_font() {
+        let screen = "\
+####.#..#.####.####.####.#..#..##..####.
+#....#..#....#.#.......#.#..#.#..#....#.
+###..####...#..###....#..####.#......#..
+#....#..#..#...#.....#...#..#.#.....#...
+#....#..#.#....#....#....#..#.#..#.#....
+####.#..#.####.#....####.#..#..##..####.";
+        let recognition = Font::SMALL.recognize(screen);
+        assert_eq!(recognition.text, "EHZFZHCZ");
+        assert!(recognition.is_complete());
+    }
+
+    #[test]
+    fn test_unknown_glyphs() {
+        let screen = "\
+####.####.####.
+#..#.#.....#...
+####.###...#...
+#..#.#.....#...
+#..#.#.....#...
+#..#.####..#...";
+        let recognition = recognize(screen).unwrap();
+        assert_eq!(recognition.text, "?E?");
+        assert_eq!(recognition.unknown, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for font in [&Font::SMALL, &Font::LARGE] {
+            let text = font.glyphs.iter().map(|(c, _)| c).collect::<String>();
+            let recognition = recognize(&font.render(&text)).unwrap();
+            assert_eq!(recognition.text, text);
+        }
+    }
+}
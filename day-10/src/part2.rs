@@ -1,6 +1,9 @@
 use itertools::Itertools;
 
-use crate::part1::Instruction;
+use crate::{
+    ocr::{self, Recognition},
+    part1::Instruction,
+};
 
 pub struct Program {
     x: i32,
@@ -37,16 +40,28 @@ impl Program {
 
         self.current += 1;
     }
+
+    /// The monitor content, one line per CRT row.
+    pub fn screen(&self) -> String {
+        self.monitor
+            .chunks(40)
+            .map(|line| line.iter().collect::<String>())
+            .join("\n")
+    }
 }
 
-pub fn render_crt(input: &str) {
+pub fn render_crt(input: &str) -> String {
     let mut program = Program::parse(input);
     for _ in 0..240 {
         program.run();
     }
-    for line in program.monitor.chunks(40) {
-        println!("{}", line.iter().collect::<String>());
-    }
+    program.screen()
+}
+
+/// Reads the letters drawn on the CRT. Unknown glyphs are shown as `?` in
+/// the text and listed in `unknown`.
+pub fn read_crt(input: &str) -> Recognition {
+    ocr::Font::SMALL.recognize(&render_crt(input))
 }
 
 #[cfg(test)]
@@ -56,7 +71,24 @@ pub mod tests {
     #[test]
     fn test_example() {
         let input = include_str!("../test.txt");
-        render_crt(input);
-        assert_eq!(1, 1);
+        assert_eq!(
+            render_crt(input),
+            "\
+##..##..##..##..##..##..##..##..##..##..
+###...###...###...###...###...###...###.
+####....####....####....####....####....
+#####.....#####.....#####.....#####.....
+######......######......######......####
+#######.......#######.......#######....."
+        );
+    }
+
+    #[test]
+    fn test_read_example() {
+        let input = include_str!("../test.txt");
+        let recognition = read_crt(input);
+        assert_eq!(recognition.text, "????????");
+        assert!(!recognition.is_complete());
+        assert_eq!(recognition.unknown.len(), 8);
     }
 }
@@ -0,0 +1,216 @@
+use std::{error::Error, fmt};
+
+use crate::worry::{Worry, WorryError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExprError {
+    MissingOperand,
+    UnexpectedToken(String),
+    UnknownOperand(String),
+    MissingClosingParenthesis,
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprError::MissingOperand => write!(f, "missing operand"),
+            ExprError::UnexpectedToken(token) => write!(f, "unexpected token '{}'", token),
+            ExprError::UnknownOperand(operand) => write!(f, "unknown operand '{}'", operand),
+            ExprError::MissingClosingParenthesis => write!(f, "missing closing parenthesis"),
+        }
+    }
+}
+
+impl Error for ExprError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinOp {
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "+" => Some(BinOp::Add),
+            "-" => Some(BinOp::Sub),
+            "*" => Some(BinOp::Mul),
+            "/" => Some(BinOp::Div),
+            _ => None,
+        }
+    }
+
+    fn precedence(&self) -> usize {
+        match self {
+            BinOp::Add | BinOp::Sub => 1,
+            BinOp::Mul | BinOp::Div => 2,
+        }
+    }
+}
+
+/**
+ * The right-hand side of a monkey operation, like `old * old + 3`.
+ * Usual precedence rules apply, and parentheses can be used to group terms.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Old,
+    Const(u64),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(input);
+        let mut position = 0;
+        let expr = parse_binary(&tokens, &mut position, 0)?;
+        match tokens.get(position) {
+            Some(token) => Err(ExprError::UnexpectedToken(token.clone())),
+            None => Ok(expr),
+        }
+    }
+
+    /// Whether the expression only adds and multiplies, so that it can be
+    /// computed modulo any number
+    pub fn is_modular(&self) -> bool {
+        match self {
+            Expr::Old | Expr::Const(_) => true,
+            Expr::Binary(BinOp::Add | BinOp::Mul, lhs, rhs) => lhs.is_modular() && rhs.is_modular(),
+            Expr::Binary(BinOp::Sub | BinOp::Div, _, _) => false,
+        }
+    }
+
+    pub fn eval<W: Worry>(&self, old: &W) -> Result<W, WorryError> {
+        match self {
+            Expr::Old => Ok(old.clone()),
+            Expr::Const(n) => Ok(W::from_u64(*n)),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(old)?, rhs.eval(old)?);
+                match op {
+                    BinOp::Add => lhs.add(&rhs),
+                    BinOp::Sub => lhs.sub(&rhs),
+                    BinOp::Mul => lhs.mul(&rhs),
+                    BinOp::Div => lhs.div(&rhs),
+                }
+            }
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Vec<String> {
+    // Operators may be attached to operands, as in `old*2`
+    input
+        .chars()
+        .map(|c| match c {
+            '+' | '-' | '*' | '/' | '(' | ')' => format!(" {} ", c),
+            c => c.to_string(),
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .map(String::from)
+        .collect()
+}
+
+/// Precedence climbing: parses operators binding tighter than `min_precedence`
+fn parse_binary(
+    tokens: &[String],
+    position: &mut usize,
+    min_precedence: usize,
+) -> Result<Expr, ExprError> {
+    let mut lhs = parse_operand(tokens, position)?;
+
+    while let Some(op) = tokens.get(*position).and_then(|t| BinOp::from_token(t)) {
+        if op.precedence() <= min_precedence {
+            break;
+        }
+        *position += 1;
+        let rhs = parse_binary(tokens, position, op.precedence())?;
+        lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+    }
+
+    Ok(lhs)
+}
+
+fn parse_operand(tokens: &[String], position: &mut usize) -> Result<Expr, ExprError> {
+    let token = tokens.get(*position).ok_or(ExprError::MissingOperand)?;
+    *position += 1;
+
+    match token.as_str() {
+        "old" => Ok(Expr::Old),
+        "(" => {
+            let expr = parse_binary(tokens, position, 0)?;
+            match tokens.get(*position).map(String::as_str) {
+                Some(")") => *position += 1,
+                _ => return Err(ExprError::MissingClosingParenthesis),
+            }
+            Ok(expr)
+        }
+        n => n
+            .parse()
+            .map(Expr::Const)
+            .map_err(|_| ExprError::UnknownOperand(n.to_string())),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use num::BigUint;
+
+    use crate::expr::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Expr::parse("old * 19").unwrap(),
+            Expr::Binary(BinOp::Mul, Box::new(Expr::Old), Box::new(Expr::Const(19)))
+        );
+        assert_eq!(Expr::parse("old*old").unwrap().eval(&7_u64), Ok(49));
+        assert_eq!(Expr::parse("old + 2 * 3").unwrap().eval(&1_u64), Ok(7));
+        assert_eq!(Expr::parse("(old + 2) * 3").unwrap().eval(&1_u64), Ok(9));
+        assert_eq!(Expr::parse("old - 10 - 2").unwrap().eval(&20_u64), Ok(8));
+        assert_eq!(Expr::parse("old / 2 / 2").unwrap().eval(&20_u64), Ok(5));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Expr::parse("old * old").unwrap().eval(&u64::MAX),
+            Err(WorryError::Overflow)
+        );
+        assert_eq!(
+            Expr::parse("old - 2").unwrap().eval(&1_u64),
+            Err(WorryError::Underflow)
+        );
+        assert_eq!(
+            Expr::parse("old / 0").unwrap().eval(&1_u64),
+            Err(WorryError::DivisionByZero)
+        );
+        assert_eq!(
+            Expr::parse("old * old")
+                .unwrap()
+                .eval(&BigUint::from(u64::MAX)),
+            Ok(BigUint::from(u64::MAX) * BigUint::from(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Expr::parse("old *"), Err(ExprError::MissingOperand));
+        assert_eq!(
+            Expr::parse("old old"),
+            Err(ExprError::UnexpectedToken("old".to_string()))
+        );
+        assert_eq!(
+            Expr::parse("(old + 1"),
+            Err(ExprError::MissingClosingParenthesis)
+        );
+        assert_eq!(
+            Expr::parse("old * new"),
+            Err(ExprError::UnknownOperand("new".to_string()))
+        );
+        assert!(Expr::parse("(old + 2) * old").unwrap().is_modular());
+        assert!(!Expr::parse("old * 2 - 1").unwrap().is_modular());
+    }
+}
@@ -1,2 +1,5 @@
+pub mod expr;
 pub mod part1;
 pub mod part2;
+pub mod trace;
+pub mod worry;
@@ -1,13 +1,164 @@
+use std::{error::Error, fmt};
+
 use itertools::Itertools;
-use lazy_static::lazy_static;
-use regex::Regex;
+use num::{integer::lcm, BigUint};
+
+use crate::{
+    expr::{Expr, ExprError},
+    trace::{PolicyTrace, Trace},
+    worry::{Worry, WorryError, WorryPolicy},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonkeyError {
+    MissingField(&'static str),
+    InvalidNumber(String),
+    InvalidOperation(ExprError),
+    /// A `divisible by 0` test
+    ZeroDivisor,
+    UnknownRule(String),
+    /// A monkey throwing to itself or to a monkey that doesn't exist
+    InvalidTarget {
+        monkey: usize,
+        target: usize,
+    },
+}
+
+impl fmt::Display for MonkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonkeyError::MissingField(field) => write!(f, "missing {}", field),
+            MonkeyError::InvalidNumber(n) => write!(f, "invalid number '{}'", n),
+            MonkeyError::InvalidOperation(e) => write!(f, "invalid operation: {}", e),
+            MonkeyError::ZeroDivisor => write!(f, "a test can't be divisible by 0"),
+            MonkeyError::UnknownRule(rule) => write!(f, "unknown monkey rule '{}'", rule),
+            MonkeyError::InvalidTarget { monkey, target } => {
+                write!(f, "monkey {} can't throw to monkey {}", monkey, target)
+            }
+        }
+    }
+}
+
+impl Error for MonkeyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulationError {
+    Parse(MonkeyError),
+    Worry(WorryError),
+    /// `ModuloLcm` with a monkey whose operation subtracts or divides, the
+    /// remainders would no longer match the real worry levels
+    NotModular(usize),
+}
+
+impl fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulationError::Parse(e) => write!(f, "{}", e),
+            SimulationError::Worry(e) => write!(f, "{}", e),
+            SimulationError::NotModular(monkey) => write!(
+                f,
+                "monkey {} operation can't be reduced modulo the LCM, only + and * can",
+                monkey
+            ),
+        }
+    }
+}
+
+impl Error for SimulationError {}
+
+impl From<MonkeyError> for SimulationError {
+    fn from(e: MonkeyError) -> Self {
+        SimulationError::Parse(e)
+    }
+}
+
+impl From<WorryError> for SimulationError {
+    fn from(e: WorryError) -> Self {
+        SimulationError::Worry(e)
+    }
+}
 
 pub fn count_monkey_business(input: &str, rounds: usize) -> usize {
-    let mut monkeys = parse_input(input);
+    simulate(input, rounds, WorryPolicy::Divide(3)).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Runs the monkeys for the given rounds, returning the monkey business level.
+/// Big integers are used when worry levels are not reduced.
+pub fn simulate(input: &str, rounds: usize, policy: WorryPolicy) -> Result<usize, SimulationError> {
+    let monkeys = parse_input(input)?;
+    check_policy(&monkeys, policy)?;
+    let business = match policy {
+        WorryPolicy::Unbounded => {
+            let monkeys = monkeys
+                .into_iter()
+                .map(|m| m.map_items(BigUint::from))
+                .collect_vec();
+            run_rounds(monkeys, rounds, policy)?
+        }
+        _ => run_rounds(monkeys, rounds, policy)?,
+    };
+    Ok(business)
+}
+
+/// Checks that the policy gives the right results with these monkeys
+pub fn check_policy<W>(monkeys: &[Monkey<W>], policy: WorryPolicy) -> Result<(), SimulationError> {
+    if policy != WorryPolicy::ModuloLcm {
+        return Ok(());
+    }
+    match monkeys.iter().position(|m| !m.operation.is_modular()) {
+        Some(monkey) => Err(SimulationError::NotModular(monkey)),
+        None => Ok(()),
+    }
+}
+
+/// Runs the monkeys like `simulate`, recording every throw. Worry levels are
+/// big integers when they are not reduced.
+pub fn trace(
+    input: &str,
+    rounds: usize,
+    policy: WorryPolicy,
+) -> Result<PolicyTrace, SimulationError> {
+    let monkeys = parse_input(input)?;
+    check_policy(&monkeys, policy)?;
+    let trace = match policy {
+        WorryPolicy::Unbounded => {
+            let monkeys = monkeys
+                .into_iter()
+                .map(|m| m.map_items(BigUint::from))
+                .collect_vec();
+            PolicyTrace::Big(run_traced_rounds(monkeys, rounds, policy)?)
+        }
+        _ => PolicyTrace::Small(run_traced_rounds(monkeys, rounds, policy)?),
+    };
+    Ok(trace)
+}
+
+fn run_traced_rounds<W: Worry>(
+    mut monkeys: Vec<Monkey<W>>,
+    rounds: usize,
+    policy: WorryPolicy,
+) -> Result<Trace<W>, WorryError> {
+    let mut trace = Trace::new(&monkeys);
     for _ in 0..rounds {
-        run_round(&mut monkeys);
+        run_traced_round(&mut monkeys, policy, Some(&mut trace))?;
     }
 
+    Ok(trace)
+}
+
+fn run_rounds<W: Worry>(
+    mut monkeys: Vec<Monkey<W>>,
+    rounds: usize,
+    policy: WorryPolicy,
+) -> Result<usize, WorryError> {
+    for _ in 0..rounds {
+        run_round(&mut monkeys, policy)?;
+    }
+
+    Ok(monkey_business(&monkeys))
+}
+
+pub fn monkey_business<W>(monkeys: &[Monkey<W>]) -> usize {
     monkeys
         .iter()
         .map(|m| m.inspected)
@@ -17,44 +168,49 @@ pub fn count_monkey_business(input: &str, rounds: usize) -> usize {
         .product()
 }
 
-fn run_round(monkeys: &mut Vec<Monkey>) {
+pub fn run_round<W: Worry>(
+    monkeys: &mut [Monkey<W>],
+    policy: WorryPolicy,
+) -> Result<(), WorryError> {
+    run_traced_round(monkeys, policy, None)
+}
+
+pub fn run_traced_round<W: Worry>(
+    monkeys: &mut [Monkey<W>],
+    policy: WorryPolicy,
+    mut trace: Option<&mut Trace<W>>,
+) -> Result<(), WorryError> {
+    if let Some(trace) = trace.as_mut() {
+        trace.begin_round();
+    }
+
+    let divisor = monkeys.iter().map(|m| m.test).fold(1, lcm);
+
     for i in 0..monkeys.len() {
         monkeys[i].inspected += monkeys[i].items.len();
 
-        while let Some(item) = monkeys[i].items.pop() {
-            let item = monkeys[i].operation.apply(item) / 3;
+        while let Some(before) = monkeys[i].items.pop() {
+            let item = policy.reduce(monkeys[i].operation.eval(&before)?, divisor)?;
 
-            let (negative, positive) = (monkeys[i].negative, monkeys[i].positive);
+            let target = match item.rem_u64(monkeys[i].test) {
+                0 => monkeys[i].positive,
+                _ => monkeys[i].negative,
+            };
 
-            match item % monkeys[i].test {
-                0 => monkeys[positive].items.push(item),
-                _ => monkeys[negative].items.push(item),
+            if let Some(trace) = trace.as_mut() {
+                trace.record(i, target, &before, &item);
             }
+            monkeys[target].items.push(item);
         }
     }
-}
 
-#[derive(Debug)]
-pub enum Operation {
-    Add(u64),
-    Multiply(u64),
-    Square,
-}
-
-impl Operation {
-    pub fn apply(&self, item: u64) -> u64 {
-        match self {
-            Operation::Add(n) => item + *n,
-            Operation::Multiply(n) => item * *n,
-            Operation::Square => item * item,
-        }
-    }
+    Ok(())
 }
 
 #[derive(Debug)]
-pub struct Monkey {
-    pub items: Vec<u64>,
-    pub operation: Operation,
+pub struct Monkey<W = u64> {
+    pub items: Vec<W>,
+    pub operation: Expr,
     pub test: u64,
     pub positive: usize,
     pub negative: usize,
@@ -62,41 +218,79 @@ pub struct Monkey {
 }
 
 impl Monkey {
-    pub fn new(data: &str) -> Self {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"Starting items: ([^\n]*)\s+Operation: new = old (\*|\+) (old|\d+)\s+Test: divisible by (\d+)\s+If true: throw to monkey (\d+)\s+If false: throw to monkey (\d+)").unwrap();
-        }
-        let captures = RE.captures(data).unwrap();
-
-        let operation = match (
-            captures.get(2).unwrap().as_str(),
-            captures.get(3).unwrap().as_str(),
-        ) {
-            ("+", n) => Operation::Add(n.parse().unwrap()),
-            ("*", "old") => Operation::Square,
-            ("*", n) => Operation::Multiply(n.parse().unwrap()),
-            _ => panic!("Unknown operation"),
+    pub fn new(data: &str) -> Result<Self, MonkeyError> {
+        let number = |s: &str| {
+            let s = s.trim();
+            s.parse()
+                .map_err(|_| MonkeyError::InvalidNumber(s.to_string()))
         };
+        let mut items = None;
+        let mut operation = None;
+        let mut test = None;
+        let mut positive = None;
+        let mut negative = None;
+
+        for line in data.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("Starting items:") {
+                items = Some(
+                    rest.split(',')
+                        .filter(|s| !s.trim().is_empty())
+                        .map(number)
+                        .collect::<Result<_, _>>()?,
+                );
+            } else if let Some(rest) = line.strip_prefix("Operation: new =") {
+                operation = Some(Expr::parse(rest).map_err(MonkeyError::InvalidOperation)?);
+            } else if let Some(rest) = line.strip_prefix("Test: divisible by") {
+                match number(rest)? {
+                    0 => return Err(MonkeyError::ZeroDivisor),
+                    n => test = Some(n),
+                }
+            } else if let Some(rest) = line.strip_prefix("If true: throw to monkey") {
+                positive = Some(number(rest)? as usize);
+            } else if let Some(rest) = line.strip_prefix("If false: throw to monkey") {
+                negative = Some(number(rest)? as usize);
+            } else if !line.is_empty() && !line.starts_with("Monkey") {
+                return Err(MonkeyError::UnknownRule(line.to_string()));
+            }
+        }
 
-        Self {
-            items: captures
-                .get(1)
-                .unwrap()
-                .as_str()
-                .split(", ")
-                .map(|s| s.parse().unwrap())
-                .collect(),
-            operation,
-            test: captures.get(4).unwrap().as_str().parse().unwrap(),
-            positive: captures.get(5).unwrap().as_str().parse::<usize>().unwrap(),
-            negative: captures.get(6).unwrap().as_str().parse::<usize>().unwrap(),
+        Ok(Self {
+            items: items.ok_or(MonkeyError::MissingField("starting items"))?,
+            operation: operation.ok_or(MonkeyError::MissingField("operation"))?,
+            test: test.ok_or(MonkeyError::MissingField("test"))?,
+            positive: positive.ok_or(MonkeyError::MissingField("true target"))?,
+            negative: negative.ok_or(MonkeyError::MissingField("false target"))?,
             inspected: 0,
+        })
+    }
+}
+
+impl<W> Monkey<W> {
+    pub fn map_items<V>(self, f: impl Fn(W) -> V) -> Monkey<V> {
+        Monkey {
+            items: self.items.into_iter().map(f).collect(),
+            operation: self.operation,
+            test: self.test,
+            positive: self.positive,
+            negative: self.negative,
+            inspected: self.inspected,
         }
     }
 }
 
-pub fn parse_input(input: &str) -> Vec<Monkey> {
-    input.split("\n\n").map(Monkey::new).collect()
+pub fn parse_input(input: &str) -> Result<Vec<Monkey>, MonkeyError> {
+    let monkeys: Vec<Monkey> = input
+        .split("\n\n")
+        .map(Monkey::new)
+        .collect::<Result<_, _>>()?;
+    for (monkey, m) in monkeys.iter().enumerate() {
+        for target in [m.positive, m.negative] {
+            if target >= monkeys.len() || target == monkey {
+                return Err(MonkeyError::InvalidTarget { monkey, target });
+            }
+        }
+    }
+    Ok(monkeys)
 }
 
 #[cfg(test)]
@@ -106,7 +300,7 @@ pub mod tests {
     #[test]
     fn test_parse_input() {
         let test = include_str!("../test.txt");
-        let monkeys = parse_input(test);
+        let monkeys = parse_input(test).unwrap();
         assert_eq!(monkeys.len(), 4);
     }
 
@@ -122,4 +316,69 @@ pub mod tests {
         let test = include_str!("../test.txt");
         assert_eq!(count_monkey_business(test, 20), 10605);
     }
+
+    #[test]
+    fn test_policies() {
+        let test = include_str!("../test.txt");
+        assert_eq!(simulate(test, 20, WorryPolicy::Unbounded), Ok(99 * 103));
+        assert_eq!(
+            simulate(test, 20, WorryPolicy::ModuloLcm),
+            simulate(test, 20, WorryPolicy::Unbounded)
+        );
+        assert_eq!(
+            run_rounds(parse_input(test).unwrap(), 20, WorryPolicy::Unbounded),
+            Err(WorryError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let test = include_str!("../test.txt");
+        let subtracting = test.replace("old + 3", "old - 3");
+        assert_eq!(
+            simulate(&subtracting, 20, WorryPolicy::ModuloLcm),
+            Err(SimulationError::NotModular(3))
+        );
+        assert_eq!(
+            trace(
+                &subtracting.replace("old * 19", "old / 2"),
+                1,
+                WorryPolicy::ModuloLcm
+            )
+            .err(),
+            Some(SimulationError::NotModular(0))
+        );
+        assert_eq!(
+            simulate(&test.replace("by 13", "by 0"), 20, WorryPolicy::ModuloLcm),
+            Err(SimulationError::Parse(MonkeyError::ZeroDivisor))
+        );
+        assert_eq!(
+            parse_input(&test.replace("old * old", "old * (old")).unwrap_err(),
+            MonkeyError::InvalidOperation(ExprError::MissingClosingParenthesis)
+        );
+    }
+
+    #[test]
+    fn test_invalid_targets() {
+        let test = include_str!("../test.txt");
+        assert_eq!(
+            parse_input(&test.replace("If true: throw to monkey 2", "If true: throw to monkey 4"))
+                .unwrap_err(),
+            MonkeyError::InvalidTarget {
+                monkey: 0,
+                target: 4
+            }
+        );
+        assert_eq!(
+            simulate(
+                &test.replace("If false: throw to monkey 3", "If false: throw to monkey 0"),
+                20,
+                WorryPolicy::Divide(3)
+            ),
+            Err(SimulationError::Parse(MonkeyError::InvalidTarget {
+                monkey: 0,
+                target: 0
+            }))
+        );
+    }
 }
@@ -0,0 +1,186 @@
+use itertools::Itertools;
+use num::BigUint;
+
+use crate::part1::Monkey;
+
+/**
+ * A single item thrown from a monkey to another one.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Throw<W> {
+    pub round: usize,
+    pub item: usize,
+    pub from: usize,
+    pub to: usize,
+    /// Worry level before the inspection
+    pub before: W,
+    /// Worry level after the operation and the relief
+    pub after: W,
+}
+
+/**
+ * Records every throw of a simulation. Items are identified by their
+ * position in the starting lists, monkey after monkey, so that item `0`
+ * is the first item held by monkey `0`.
+ */
+#[derive(Debug, Clone)]
+pub struct Trace<W> {
+    pub throws: Vec<Throw<W>>,
+    /// Items inspected by each monkey, for each round
+    pub inspections: Vec<Vec<usize>>,
+    starts: Vec<usize>,
+    holders: Vec<Vec<usize>>,
+}
+
+impl<W: Clone> Trace<W> {
+    pub fn new(monkeys: &[Monkey<W>]) -> Self {
+        let mut starts = vec![];
+        let holders = monkeys
+            .iter()
+            .enumerate()
+            .map(|(i, monkey)| {
+                let ids = (starts.len()..starts.len() + monkey.items.len()).collect_vec();
+                starts.extend(ids.iter().map(|_| i));
+                ids
+            })
+            .collect_vec();
+
+        Self {
+            throws: vec![],
+            inspections: vec![],
+            starts,
+            holders,
+        }
+    }
+
+    pub fn begin_round(&mut self) {
+        self.inspections.push(vec![0; self.holders.len()]);
+    }
+
+    /// Records the throw of the last item held by `from`, which is the one
+    /// inspected next by the simulation.
+    pub fn record(&mut self, from: usize, to: usize, before: &W, after: &W) {
+        let item = self.holders[from].pop().expect("Monkey has no items");
+        self.holders[to].push(item);
+        self.inspections.last_mut().expect("Round not started")[from] += 1;
+        self.throws.push(Throw {
+            round: self.inspections.len(),
+            item,
+            from,
+            to,
+            before: before.clone(),
+            after: after.clone(),
+        });
+    }
+
+    pub fn item_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns the monkeys holding the item, as `(round, monkey)` pairs,
+    /// starting with the monkey holding it at round 0.
+    pub fn item_path(&self, item: usize) -> Vec<(usize, usize)> {
+        let mut path = vec![(0, self.starts[item])];
+        path.extend(
+            self.throws
+                .iter()
+                .filter(|throw| throw.item == item)
+                .map(|throw| (throw.round, throw.to)),
+        );
+        path
+    }
+
+    /// Exports the per-round inspection counts, one row per round.
+    pub fn inspections_csv(&self) -> String {
+        let header = (0..self.holders.len())
+            .map(|i| format!("monkey_{}", i))
+            .join(",");
+        let rows = self
+            .inspections
+            .iter()
+            .enumerate()
+            .map(|(round, counts)| format!("{},{}", round + 1, counts.iter().join(",")));
+
+        std::iter::once(format!("round,{}", header))
+            .chain(rows)
+            .join("\n")
+    }
+}
+
+/// A trace with the worry levels used by the policy, as chosen by `trace`
+#[derive(Debug, Clone)]
+pub enum PolicyTrace {
+    Small(Trace<u64>),
+    /// Worry levels that are not reduced
+    Big(Trace<BigUint>),
+}
+
+impl PolicyTrace {
+    pub fn inspections(&self) -> &[Vec<usize>] {
+        match self {
+            PolicyTrace::Small(trace) => &trace.inspections,
+            PolicyTrace::Big(trace) => &trace.inspections,
+        }
+    }
+
+    pub fn inspections_csv(&self) -> String {
+        match self {
+            PolicyTrace::Small(trace) => trace.inspections_csv(),
+            PolicyTrace::Big(trace) => trace.inspections_csv(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use num::BigUint;
+
+    use crate::{part1::trace, trace::PolicyTrace, worry::WorryPolicy};
+
+    #[test]
+    fn test_trace_example() {
+        let input = include_str!("../test.txt");
+        let PolicyTrace::Small(trace) = trace(input, 20, WorryPolicy::Divide(3)).unwrap() else {
+            panic!("Expected small worry levels");
+        };
+
+        assert_eq!(trace.item_count(), 10);
+        assert_eq!(trace.inspections.len(), 20);
+        let totals = (0..4)
+            .map(|m| trace.inspections.iter().map(|r| r[m]).sum::<usize>())
+            .collect::<Vec<_>>();
+        assert_eq!(totals, vec![101, 95, 7, 105]);
+
+        // Monkey 0 inspects its last item first, 98
+        let first = &trace.throws[0];
+        assert_eq!((first.item, first.from, first.to), (1, 0, 3));
+        assert_eq!((first.before, first.after), (98, 620));
+        assert_eq!(trace.item_path(1)[..2], [(0, 0), (1, 3)]);
+
+        let csv = trace.inspections_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("round,monkey_0,monkey_1,monkey_2,monkey_3")
+        );
+        assert_eq!(lines.next(), Some("1,2,4,3,5"));
+    }
+
+    #[test]
+    fn test_trace_unbounded() {
+        let input = include_str!("../test.txt");
+        let PolicyTrace::Big(big) = trace(input, 20, WorryPolicy::Unbounded).unwrap() else {
+            panic!("Expected big worry levels");
+        };
+        let totals = (0..4)
+            .map(|m| big.inspections.iter().map(|r| r[m]).sum::<usize>())
+            .collect::<Vec<_>>();
+        assert_eq!(totals, vec![99, 97, 8, 103]);
+        // Worry levels past the range of a u64, where `Trace<u64>` overflowed
+        let max = BigUint::from(u64::MAX);
+        assert!(big.throws.iter().any(|throw| throw.after > max));
+
+        let modular = trace(input, 20, WorryPolicy::ModuloLcm).unwrap();
+        assert_eq!(modular.inspections(), big.inspections.as_slice());
+    }
+}
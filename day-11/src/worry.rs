@@ -0,0 +1,112 @@
+use std::fmt::{self, Debug, Display};
+
+use num::{BigUint, ToPrimitive};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorryError {
+    Overflow,
+    Underflow,
+    DivisionByZero,
+}
+
+impl Display for WorryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorryError::Overflow => write!(f, "worry level overflow"),
+            WorryError::Underflow => write!(f, "worry level below zero"),
+            WorryError::DivisionByZero => write!(f, "worry level divided by zero"),
+        }
+    }
+}
+
+impl std::error::Error for WorryError {}
+
+/**
+ * A worry level. Every operation is checked, so that a rule set that would
+ * overflow (or go below zero) is reported instead of silently wrapping.
+ */
+pub trait Worry: Clone + Debug + Display + PartialEq {
+    fn from_u64(n: u64) -> Self;
+    fn add(&self, rhs: &Self) -> Result<Self, WorryError>;
+    fn sub(&self, rhs: &Self) -> Result<Self, WorryError>;
+    fn mul(&self, rhs: &Self) -> Result<Self, WorryError>;
+    fn div(&self, rhs: &Self) -> Result<Self, WorryError>;
+    fn rem_u64(&self, n: u64) -> u64;
+}
+
+impl Worry for u64 {
+    fn from_u64(n: u64) -> Self {
+        n
+    }
+
+    fn add(&self, rhs: &Self) -> Result<Self, WorryError> {
+        self.checked_add(*rhs).ok_or(WorryError::Overflow)
+    }
+
+    fn sub(&self, rhs: &Self) -> Result<Self, WorryError> {
+        self.checked_sub(*rhs).ok_or(WorryError::Underflow)
+    }
+
+    fn mul(&self, rhs: &Self) -> Result<Self, WorryError> {
+        self.checked_mul(*rhs).ok_or(WorryError::Overflow)
+    }
+
+    fn div(&self, rhs: &Self) -> Result<Self, WorryError> {
+        self.checked_div(*rhs).ok_or(WorryError::DivisionByZero)
+    }
+
+    fn rem_u64(&self, n: u64) -> u64 {
+        self % n
+    }
+}
+
+impl Worry for BigUint {
+    fn from_u64(n: u64) -> Self {
+        BigUint::from(n)
+    }
+
+    fn add(&self, rhs: &Self) -> Result<Self, WorryError> {
+        Ok(self + rhs)
+    }
+
+    fn sub(&self, rhs: &Self) -> Result<Self, WorryError> {
+        num::CheckedSub::checked_sub(self, rhs).ok_or(WorryError::Underflow)
+    }
+
+    fn mul(&self, rhs: &Self) -> Result<Self, WorryError> {
+        Ok(self * rhs)
+    }
+
+    fn div(&self, rhs: &Self) -> Result<Self, WorryError> {
+        num::CheckedDiv::checked_div(self, rhs).ok_or(WorryError::DivisionByZero)
+    }
+
+    fn rem_u64(&self, n: u64) -> u64 {
+        (self % n).to_u64().unwrap()
+    }
+}
+
+/**
+ * How worry levels are kept manageable after every inspection.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorryPolicy {
+    /// Relief: the worry level is divided by `k` (part 1 uses 3)
+    Divide(u64),
+    /// The worry level is reduced modulo the LCM of all the monkeys tests,
+    /// which keeps every test result unchanged as long as operations only
+    /// add and multiply
+    ModuloLcm,
+    /// The worry level is left untouched, it should be used with big integers
+    Unbounded,
+}
+
+impl WorryPolicy {
+    pub fn reduce<W: Worry>(&self, worry: W, lcm: u64) -> Result<W, WorryError> {
+        match self {
+            WorryPolicy::Divide(k) => worry.div(&W::from_u64(*k)),
+            WorryPolicy::ModuloLcm => Ok(W::from_u64(worry.rem_u64(lcm))),
+            WorryPolicy::Unbounded => Ok(worry),
+        }
+    }
+}
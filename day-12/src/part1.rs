@@ -1,18 +1,26 @@
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::HashMap;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+use crate::route::ClimbRule;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
 pub struct Pos {
-    x: i32,
-    y: i32,
+    pub x: i32,
+    pub y: i32,
 }
 
 impl Pos {
     pub fn new(x: i32, y: i32) -> Self {
-        Self {
-            x: x as i32,
-            y: y as i32,
-        }
+        Self { x, y }
+    }
+
+    pub fn neighbours(&self) -> Vec<Pos> {
+        vec![
+            Pos::new(self.x, self.y - 1),
+            Pos::new(self.x, self.y + 1),
+            Pos::new(self.x - 1, self.y),
+            Pos::new(self.x + 1, self.y),
+        ]
     }
 }
 
@@ -49,55 +57,25 @@ impl MapGraph {
     }
 
     pub fn visitable_neighbours(&self, pos: &Pos) -> Vec<(Pos, usize)> {
-        vec![
-            Pos::new(pos.x, pos.y - 1),
-            Pos::new(pos.x, pos.y + 1),
-            Pos::new(pos.x - 1, pos.y),
-            Pos::new(pos.x + 1, pos.y),
-        ]
-        .iter()
-        .filter_map(|p| self.map.get(p).map(|height| (*p, *height)))
-        .filter(|(_, height)| *height <= self.map[pos] + 1)
-        .collect_vec()
+        self.neighbours(pos, &ClimbRule::default())
     }
 
+    /// Positions from which `pos` can be reached in one step
     pub fn descendable_neighbours(&self, pos: &Pos) -> Vec<(Pos, usize)> {
-        vec![
-            Pos::new(pos.x, pos.y - 1),
-            Pos::new(pos.x, pos.y + 1),
-            Pos::new(pos.x - 1, pos.y),
-            Pos::new(pos.x + 1, pos.y),
-        ]
-        .iter()
-        .filter_map(|p| self.map.get(p).map(|height| (*p, *height)))
-        .filter(|(_, height)| *height >= self.map[pos] - 1)
-        .collect_vec()
+        let rule = ClimbRule::default();
+        pos.neighbours()
+            .into_iter()
+            .filter_map(|p| self.map.get(&p).map(|height| (p, *height)))
+            .filter(|(_, height)| rule.allows(*height, self.map[pos]))
+            .collect_vec()
     }
 }
 
 pub fn shortest_path(input: &str) -> Option<usize> {
     let graph = MapGraph::parse(input);
-
-    let mut queue = VecDeque::new();
-    let mut visited = HashSet::new();
-    queue.push_back((graph.start, 0));
-    visited.insert(graph.start);
-
-    while let Some((pos, len)) = queue.pop_front() {
-        // println!("Visiting {:?} (len: {}) end={:?}", pos, len, graph.end);
-        if pos == graph.end {
-            return Some(len);
-        }
-
-        for (neighbour, _) in graph.visitable_neighbours(&pos) {
-            if !visited.contains(&neighbour) {
-                visited.insert(neighbour);
-                queue.push_back((neighbour, len + 1));
-            }
-        }
-    }
-
-    None
+    graph
+        .route(graph.start, graph.end, &ClimbRule::default())
+        .map(|route| route.len())
 }
 
 #[cfg(test)]
@@ -109,4 +87,14 @@ pub mod tests {
         let input = include_str!("../test.txt");
         assert_eq!(shortest_path(input), Some(31));
     }
+
+    #[test]
+    fn test_descendable_from_lowest() {
+        let graph = MapGraph::parse(include_str!("../test.txt"));
+        // The start has height 0, where the old rule underflowed
+        assert_eq!(
+            graph.descendable_neighbours(&graph.start),
+            vec![(Pos::new(0, 1), 0), (Pos::new(1, 0), 0)]
+        );
+    }
 }
@@ -0,0 +1,228 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use itertools::Itertools;
+
+use crate::part1::{MapGraph, Pos};
+
+/// How much the height can change in a single step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClimbRule {
+    pub max_up: usize,
+    /// `None` to allow any drop
+    pub max_down: Option<usize>,
+}
+
+impl Default for ClimbRule {
+    /// The puzzle rule: at most one up, any amount down
+    fn default() -> Self {
+        Self {
+            max_up: 1,
+            max_down: None,
+        }
+    }
+}
+
+impl ClimbRule {
+    pub fn allows(&self, from: usize, to: usize) -> bool {
+        if to >= from {
+            to - from <= self.max_up
+        } else {
+            self.max_down.is_none_or(|max| from - to <= max)
+        }
+    }
+}
+
+/// Positions from the start to the end of a route, both included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub steps: Vec<Pos>,
+}
+
+impl Route {
+    /// Number of steps taken
+    pub fn len(&self) -> usize {
+        self.steps.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Draws the route over the height map: an arrow on each position of
+    /// the route towards the next one, `E` at the end, and the height letter
+    /// of every other position
+    pub fn render(&self, graph: &MapGraph) -> String {
+        let width = graph.map.keys().map(|p| p.x).max().unwrap_or(-1) + 1;
+        let height = graph.map.keys().map(|p| p.y).max().unwrap_or(-1) + 1;
+        let mut marks: HashMap<Pos, char> = self
+            .steps
+            .iter()
+            .tuple_windows()
+            .map(|(from, to)| {
+                let arrow = match (to.x - from.x, to.y - from.y) {
+                    (0, -1) => '^',
+                    (0, 1) => 'v',
+                    (-1, 0) => '<',
+                    _ => '>',
+                };
+                (*from, arrow)
+            })
+            .collect();
+        if let Some(last) = self.steps.last() {
+            marks.insert(*last, 'E');
+        }
+
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let pos = Pos::new(x, y);
+                        match (marks.get(&pos), graph.map.get(&pos)) {
+                            (Some(mark), _) => *mark,
+                            (None, Some(height)) => (b'a' + *height as u8) as char,
+                            (None, None) => ' ',
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+}
+
+impl MapGraph {
+    /// Positions reachable from `pos` in one step
+    pub fn neighbours(&self, pos: &Pos, rule: &ClimbRule) -> Vec<(Pos, usize)> {
+        let height = self.map[pos];
+        pos.neighbours()
+            .into_iter()
+            .filter_map(|p| self.map.get(&p).map(|h| (p, *h)))
+            .filter(|(_, h)| rule.allows(height, *h))
+            .collect_vec()
+    }
+
+    /// Shortest route from any of the `sources` to `to`, in a single A*
+    /// search seeded with all of them. Manhattan distance never exceeds the
+    /// remaining steps, so the first time `to` is popped the route is optimal.
+    pub fn route_from_any(&self, sources: &[Pos], to: Pos, rule: &ClimbRule) -> Option<Route> {
+        let estimate = |p: &Pos| ((p.x - to.x).abs() + (p.y - to.y).abs()) as usize;
+        let mut queue = BinaryHeap::new();
+        let mut costs: HashMap<Pos, usize> = HashMap::new();
+        let mut previous: HashMap<Pos, Pos> = HashMap::new();
+
+        for source in sources.iter().filter(|s| self.map.contains_key(s)) {
+            costs.insert(*source, 0);
+            queue.push(Reverse((estimate(source), 0, *source)));
+        }
+
+        while let Some(Reverse((_, cost, pos))) = queue.pop() {
+            if pos == to {
+                let mut steps = vec![pos];
+                while let Some(prev) = previous.get(steps.last().unwrap()) {
+                    steps.push(*prev);
+                }
+                steps.reverse();
+                return Some(Route { steps });
+            }
+            if costs.get(&pos).is_some_and(|c| *c < cost) {
+                continue;
+            }
+
+            for (neighbour, _) in self.neighbours(&pos, rule) {
+                let next = cost + 1;
+                if costs.get(&neighbour).is_none_or(|c| next < *c) {
+                    costs.insert(neighbour, next);
+                    previous.insert(neighbour, pos);
+                    queue.push(Reverse((next + estimate(&neighbour), next, neighbour)));
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn route(&self, from: Pos, to: Pos, rule: &ClimbRule) -> Option<Route> {
+        self.route_from_any(&[from], to, rule)
+    }
+
+    /// Positions at the given height, e.g. every `a` for height 0
+    pub fn positions_at(&self, height: usize) -> Vec<Pos> {
+        self.map
+            .iter()
+            .filter(|(_, h)| **h == height)
+            .map(|(p, _)| *p)
+            .sorted_by_key(|p| (p.y, p.x))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::route::*;
+
+    #[test]
+    fn test_route() {
+        let graph = MapGraph::parse(include_str!("../test.txt"));
+        let route = graph
+            .route(graph.start, graph.end, &ClimbRule::default())
+            .unwrap();
+        assert_eq!(route.len(), 31);
+        assert_eq!(route.steps[0], graph.start);
+
+        // Every step follows the rule
+        for (a, b) in route.steps.iter().tuple_windows() {
+            assert_eq!((a.x - b.x).abs() + (a.y - b.y).abs(), 1);
+            assert!(graph.map[b] <= graph.map[a] + 1);
+        }
+        let rendered = route.render(&graph);
+        assert_eq!(rendered.lines().count(), 5);
+        assert_eq!(rendered.lines().nth(2).unwrap().chars().nth(5), Some('E'));
+        let marks = rendered.chars().filter(|c| "^v<>E".contains(*c)).count();
+        assert_eq!(marks, 32);
+
+        // Positions off the route keep their height
+        for (line, input) in rendered.lines().zip(include_str!("../test.txt").lines()) {
+            for (rendered, height) in line.chars().zip(input.chars()) {
+                if !"^v<>E".contains(rendered) {
+                    assert_eq!(rendered, height);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_source() {
+        let graph = MapGraph::parse(include_str!("../test.txt"));
+        let sources = graph.positions_at(0);
+        let route = graph
+            .route_from_any(&sources, graph.end, &ClimbRule::default())
+            .unwrap();
+        assert_eq!(route.len(), 29);
+        assert_eq!(graph.map[&route.steps[0]], 0);
+    }
+
+    #[test]
+    fn test_climb_rule() {
+        let graph = MapGraph::parse("Sbcd\nzzzE");
+        // Gentle steps can climb `abcd`, but not up to the `z` row
+        let gentle = ClimbRule {
+            max_up: 1,
+            max_down: Some(1),
+        };
+        assert_eq!(graph.route(graph.start, graph.end, &gentle), None);
+
+        let steep = ClimbRule {
+            max_up: 25,
+            max_down: Some(0),
+        };
+        assert_eq!(
+            graph.route(graph.start, graph.end, &steep).map(|r| r.len()),
+            Some(4)
+        );
+        assert!(ClimbRule::default().allows(0, 1));
+        assert!(!ClimbRule::default().allows(0, 2));
+        assert!(ClimbRule::default().allows(25, 0));
+    }
+}
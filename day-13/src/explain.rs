@@ -0,0 +1,253 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+};
+
+use crate::packet::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Left => write!(f, "left"),
+            Side::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// An integer wrapped in a list to be compared with a list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Promotion {
+    /// Indexes leading to the promoted integer
+    pub path: Vec<usize>,
+    pub side: Side,
+    pub value: i64,
+}
+
+/// The comparison that decided the order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Ints {
+        left: i64,
+        right: i64,
+    },
+    /// A side ran out of items, with the length of both lists
+    RanOut {
+        side: Side,
+        left: usize,
+        right: usize,
+    },
+    /// Nothing decided, the packets are equal
+    Equal,
+}
+
+/**
+ * Why two packets are in a given order: the decisive comparison, the
+ * indexes leading to it and the integers promoted to lists on the way.
+ * It displays as the indented trace of the puzzle description.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub order: Ordering,
+    pub path: Vec<usize>,
+    pub decision: Decision,
+    pub promotions: Vec<Promotion>,
+    /// Lines of the trace, with their depth
+    pub trace: Vec<(usize, String)>,
+}
+
+impl Explanation {
+    pub fn is_ordered(&self) -> bool {
+        self.order == Ordering::Less
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (depth, line) in self.trace.iter() {
+            writeln!(f, "{}- {}", "  ".repeat(*depth), line)?;
+        }
+        Ok(())
+    }
+}
+
+struct Explainer {
+    path: Vec<usize>,
+    promotions: Vec<Promotion>,
+    trace: Vec<(usize, String)>,
+    decision: Option<(Vec<usize>, Decision)>,
+}
+
+impl Explainer {
+    fn log(&mut self, depth: usize, line: String) {
+        self.trace.push((depth, line));
+    }
+
+    fn decide(&mut self, depth: usize, decision: Decision, order: Ordering) -> Ordering {
+        let reason = match &decision {
+            Decision::Ints { .. } if order == Ordering::Less => "Left side is smaller".to_string(),
+            Decision::Ints { .. } => "Right side is smaller".to_string(),
+            Decision::RanOut { side, .. } => {
+                let side = if *side == Side::Left { "Left" } else { "Right" };
+                format!("{} side ran out of items", side)
+            }
+            Decision::Equal => unreachable!(),
+        };
+        let result = if order == Ordering::Less {
+            "inputs are in the right order"
+        } else {
+            "inputs are not in the right order"
+        };
+        self.log(depth + 1, format!("{}, so {}", reason, result));
+        self.decision = Some((self.path.clone(), decision));
+        order
+    }
+
+    fn compare(&mut self, left: &Value, right: &Value, depth: usize) -> Ordering {
+        self.log(depth, format!("Compare {} vs {}", left, right));
+        match (left, right) {
+            (Value::Int(l), Value::Int(r)) => match l.cmp(r) {
+                Ordering::Equal => Ordering::Equal,
+                order => self.decide(
+                    depth,
+                    Decision::Ints {
+                        left: *l,
+                        right: *r,
+                    },
+                    order,
+                ),
+            },
+            (Value::List(l), Value::List(r)) => {
+                for (i, (a, b)) in l.iter().zip(r.iter()).enumerate() {
+                    self.path.push(i);
+                    let order = self.compare(a, b, depth + 1);
+                    self.path.pop();
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                }
+                let ran_out = |side| Decision::RanOut {
+                    side,
+                    left: l.len(),
+                    right: r.len(),
+                };
+                match l.len().cmp(&r.len()) {
+                    Ordering::Equal => Ordering::Equal,
+                    Ordering::Less => self.decide(depth, ran_out(Side::Left), Ordering::Less),
+                    Ordering::Greater => {
+                        self.decide(depth, ran_out(Side::Right), Ordering::Greater)
+                    }
+                }
+            }
+            (Value::Int(value), Value::List(_)) | (Value::List(_), Value::Int(value)) => {
+                let side = if matches!(left, Value::Int(_)) {
+                    Side::Left
+                } else {
+                    Side::Right
+                };
+                let promoted = Value::List(vec![Value::Int(*value)]);
+                self.log(
+                    depth + 1,
+                    format!(
+                        "Mixed types; convert {} to {} and retry comparison",
+                        side, promoted
+                    ),
+                );
+                self.promotions.push(Promotion {
+                    path: self.path.clone(),
+                    side,
+                    value: *value,
+                });
+                match side {
+                    Side::Left => self.compare(&promoted, right, depth + 1),
+                    Side::Right => self.compare(left, &promoted, depth + 1),
+                }
+            }
+        }
+    }
+}
+
+/// Compares two packets, recording how the order was decided. Indexes of
+/// promoted integers count as index 0 of their new list.
+pub fn explain(left: &Value, right: &Value) -> Explanation {
+    let mut explainer = Explainer {
+        path: vec![],
+        promotions: vec![],
+        trace: vec![],
+        decision: None,
+    };
+    let order = explainer.compare(left, right, 0);
+    let (path, decision) = explainer.decision.unwrap_or((vec![], Decision::Equal));
+
+    Explanation {
+        order,
+        path,
+        decision,
+        promotions: explainer.promotions,
+        trace: explainer.trace,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{explain::*, part1::parse_pairs};
+
+    #[test]
+    fn test_trace() {
+        let pairs = parse_pairs(include_str!("../test.txt"));
+        let explanation = explain(&pairs[1].0, &pairs[1].1);
+        assert_eq!(
+            explanation.to_string(),
+            "- Compare [[1],[2,3,4]] vs [[1],4]
+  - Compare [1] vs [1]
+    - Compare 1 vs 1
+  - Compare [2,3,4] vs 4
+    - Mixed types; convert right to [4] and retry comparison
+    - Compare [2,3,4] vs [4]
+      - Compare 2 vs 4
+        - Left side is smaller, so inputs are in the right order
+"
+        );
+        assert_eq!(explanation.path, vec![1, 0]);
+        assert_eq!(explanation.decision, Decision::Ints { left: 2, right: 4 });
+        assert_eq!(
+            explanation.promotions,
+            vec![Promotion {
+                path: vec![1],
+                side: Side::Right,
+                value: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decisions() {
+        let pairs = parse_pairs(include_str!("../test.txt"));
+        let explanations = pairs.iter().map(|(l, r)| explain(l, r)).collect::<Vec<_>>();
+        for (explanation, (l, r)) in explanations.iter().zip(pairs.iter()) {
+            assert_eq!(explanation.order, l.cmp(r));
+        }
+
+        // [[4,4],4,4] vs [[4,4],4,4,4]
+        assert_eq!(
+            explanations[3].decision,
+            Decision::RanOut {
+                side: Side::Left,
+                left: 3,
+                right: 4
+            }
+        );
+        assert!(explanations[3].path.is_empty());
+        // [[[]]] vs [[]]
+        assert_eq!(explanations[6].path, vec![0]);
+        assert!(!explanations[6].is_ordered());
+
+        let same = explain(&pairs[0].0, &pairs[0].0);
+        assert_eq!(same.decision, Decision::Equal);
+    }
+}
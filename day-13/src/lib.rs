@@ -1,2 +1,4 @@
+pub mod explain;
+pub mod packet;
 pub mod part1;
 pub mod part2;
@@ -0,0 +1,316 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt::{self, Display},
+    iter::Peekable,
+    slice,
+    str::{CharIndices, FromStr},
+};
+
+use itertools::Itertools;
+
+/**
+ * A packet value. Values are ordered as in the puzzle: integers by value,
+ * lists element by element, and an integer compared with a list as if it
+ * were a list with only that integer. Equality follows the same rule, so
+ * `[1]` and `1` are equal. For that reason `Hash` isn't implemented: equal
+ * values don't share a structure to hash. Compare `to_string()` to check
+ * the structure of a value.
+ */
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    List(Vec<Value>),
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::List(a), Value::List(b)) => a.as_slice().cmp(b.as_slice()),
+            (Value::Int(_), Value::List(b)) => slice::from_ref(self).cmp(b.as_slice()),
+            (Value::List(a), Value::Int(_)) => a.as_slice().cmp(slice::from_ref(other)),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::List(list) => write!(f, "[{}]", list.iter().join(",")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Empty,
+    UnexpectedChar(char),
+    /// The input ended inside a list
+    UnexpectedEnd,
+    InvalidInt(String),
+}
+
+/// A parse error, with the byte offset where it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::Empty => write!(f, "empty packet"),
+            ParseErrorKind::UnexpectedChar(c) => {
+                write!(f, "unexpected '{}' at position {}", c, self.position)
+            }
+            ParseErrorKind::UnexpectedEnd => {
+                write!(
+                    f,
+                    "unclosed list, input ended at position {}",
+                    self.position
+                )
+            }
+            ParseErrorKind::InvalidInt(value) => {
+                write!(
+                    f,
+                    "invalid integer '{}' at position {}",
+                    value, self.position
+                )
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&mut self, kind: ParseErrorKind) -> ParseError {
+        let position = self.position();
+        ParseError { position, kind }
+    }
+
+    fn position(&mut self) -> usize {
+        self.chars.peek().map_or(self.input.len(), |(i, _)| *i)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    /// Next non-whitespace character, without consuming it
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    fn unexpected(&mut self) -> ParseError {
+        match self.peek() {
+            Some(c) => self.error(ParseErrorKind::UnexpectedChar(c)),
+            None => self.error(ParseErrorKind::UnexpectedEnd),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        match self.peek() {
+            Some('[') => self.list(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.int(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn int(&mut self) -> Result<Value, ParseError> {
+        let start = self.position();
+        let mut end = start;
+        while let Some((i, c)) = self
+            .chars
+            .next_if(|(i, c)| c.is_ascii_digit() || (*c == '-' && *i == start))
+        {
+            end = i + c.len_utf8();
+        }
+        let text = &self.input[start..end];
+        text.parse().map(Value::Int).map_err(|_| ParseError {
+            position: start,
+            kind: ParseErrorKind::InvalidInt(text.to_string()),
+        })
+    }
+
+    fn list(&mut self) -> Result<Value, ParseError> {
+        self.chars.next();
+        let mut list = vec![];
+        if self.peek() == Some(']') {
+            self.chars.next();
+            return Ok(Value::List(list));
+        }
+
+        loop {
+            list.push(self.value()?);
+            match self.peek() {
+                Some(',') => {
+                    self.chars.next();
+                }
+                Some(']') => {
+                    self.chars.next();
+                    return Ok(Value::List(list));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+}
+
+impl FromStr for Value {
+    type Err = ParseError;
+
+    /// Parses a packet, allowing whitespace between tokens and negative
+    /// integers
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            input,
+            chars: input.char_indices().peekable(),
+        };
+        if parser.peek().is_none() {
+            return Err(parser.error(ParseErrorKind::Empty));
+        }
+        let value = parser.value()?;
+        match parser.peek() {
+            None => Ok(value),
+            Some(_) => Err(parser.unexpected()),
+        }
+    }
+}
+
+/// A JSON value that isn't made only of arrays and integers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError(pub String);
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a packet value: {}", self.0)
+    }
+}
+
+impl Error for JsonError {}
+
+impl From<&Value> for serde_json::Value {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Int(value) => serde_json::Value::from(*value),
+            Value::List(list) => serde_json::Value::Array(list.iter().map(Into::into).collect()),
+        }
+    }
+}
+
+impl TryFrom<&serde_json::Value> for Value {
+    type Error = JsonError;
+
+    fn try_from(json: &serde_json::Value) -> Result<Self, Self::Error> {
+        match json {
+            serde_json::Value::Number(n) => n
+                .as_i64()
+                .map(Value::Int)
+                .ok_or_else(|| JsonError(n.to_string())),
+            serde_json::Value::Array(items) => items
+                .iter()
+                .map(Value::try_from)
+                .collect::<Result<_, _>>()
+                .map(Value::List),
+            other => Err(JsonError(other.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::packet::*;
+
+    fn parse(input: &str) -> Value {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(parse("[1,1,3,1,1]") < parse("[1,1,5,1,1]"));
+        assert!(parse("[[1],[2,3,4]]") < parse("[[1],4]"));
+        assert!(parse("[9]") > parse("[[8,7,6]]"));
+        assert!(parse("[[[]]]") > parse("[[]]"));
+        assert_eq!(parse("[[1]]"), parse("1"));
+        assert_eq!(parse("[]").cmp(&parse("[[]]")), Ordering::Less);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for input in include_str!("../test.txt")
+            .lines()
+            .filter(|l| !l.is_empty())
+        {
+            assert_eq!(parse(input).to_string(), input);
+        }
+        assert_eq!(parse(" [ -1 , [ 2 ] ,[]] ").to_string(), "[-1,[2],[]]");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |input: &str| input.parse::<Value>().unwrap_err();
+        assert_eq!(
+            error("[1,2"),
+            ParseError {
+                position: 4,
+                kind: ParseErrorKind::UnexpectedEnd
+            }
+        );
+        assert_eq!(
+            error("[1]]"),
+            ParseError {
+                position: 3,
+                kind: ParseErrorKind::UnexpectedChar(']')
+            }
+        );
+        assert_eq!(error("[1,,2]").to_string(), "unexpected ',' at position 3");
+        assert_eq!(
+            error("[1,-]").kind,
+            ParseErrorKind::InvalidInt("-".to_string())
+        );
+        assert_eq!(error("  ").kind, ParseErrorKind::Empty);
+    }
+
+    #[test]
+    fn test_json() {
+        let value = parse("[1,[2,[-3]],[]]");
+        let json = serde_json::Value::from(&value);
+        assert_eq!(json, serde_json::json!([1, [2, [-3]], []]));
+        assert_eq!(
+            Value::try_from(&json).unwrap().to_string(),
+            "[1,[2,[-3]],[]]"
+        );
+        // The nesting is kept, even though `[[1]]` equals `1`
+        let nested = Value::try_from(&serde_json::json!([[1]])).unwrap();
+        assert_eq!(nested.to_string(), "[[1]]");
+        assert_eq!(serde_json::Value::from(&nested), serde_json::json!([[1]]));
+        assert_eq!(
+            Value::try_from(&serde_json::json!([1, "2"])),
+            Err(JsonError("\"2\"".to_string()))
+        );
+    }
+}
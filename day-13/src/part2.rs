@@ -1,20 +1,20 @@
-use crate::part1::{compare_pair, parse_pairs, Value};
+use crate::part1::{parse_pairs, Value};
 
+/// Index (1-based) the dividers get once all packets are sorted, counting
+/// the packets before each of them instead of sorting
 pub fn decoder_packet_indices(input: &str) -> usize {
     let pairs = parse_pairs(input);
-    let mut pairs = pairs
+    let packets = pairs
         .iter()
-        .flat_map(|(p0, p1)| vec![p0, p1])
+        .flat_map(|(p0, p1)| [p0, p1])
         .collect::<Vec<_>>();
 
-    let packet2 = Value::List(vec![Value::List(vec![Value::Int(2)])]);
-    let packet6 = Value::List(vec![Value::List(vec![Value::Int(6)])]);
-    pairs.extend(vec![&packet2, &packet6]);
+    let packet2 = Value::parse("[[2]]");
+    let packet6 = Value::parse("[[6]]");
 
-    pairs.sort_by(|&a, &b| compare_pair(&(a.clone(), b.clone())));
-
-    let index2 = pairs.iter().position(|&p| p == &packet2).unwrap() + 1;
-    let index6 = pairs.iter().position(|&p| p == &packet6).unwrap() + 1;
+    let index2 = packets.iter().filter(|&&p| p < &packet2).count() + 1;
+    // `[[2]]` comes before `[[6]]` too
+    let index6 = packets.iter().filter(|&&p| p < &packet6).count() + 2;
 
     index2 * index6
 }
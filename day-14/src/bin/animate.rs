@@ -0,0 +1,63 @@
+use day_14::{
+    part1::scan_rock_paths,
+    sand::{SandGrid, SOURCE},
+};
+use toolkit::animation::{Animation, Frame, Palette};
+
+/// Writes the sand filling the cave as an animation, usage:
+/// `cargo run -p day-14 --bin animate -- [input] [output.gif|output.png|dir] [--floor] [--every N]`
+fn main() {
+    let mut paths = vec![];
+    let mut has_floor = false;
+    let mut every = 1;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--floor" => has_floor = true,
+            "--every" => {
+                every = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| *n > 0)
+                    .expect("Invalid number of grains per frame")
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let mut paths = paths.into_iter();
+    let input = match paths.next() {
+        Some(path) => std::fs::read_to_string(path).expect("Cannot read input"),
+        None => include_str!("../../test.txt").to_string(),
+    };
+    let output = paths.next().unwrap_or_else(|| "sand.gif".to_string());
+
+    let scan = scan_rock_paths(&input);
+    let mut grid = SandGrid::new(&scan, has_floor, &[SOURCE]).unwrap_or_else(|e| panic!("{}", e));
+    let palette = Palette::by_char(
+        [20, 20, 30],
+        &[
+            ('#', [120, 110, 100]),
+            ('o', [230, 200, 90]),
+            ('+', [220, 60, 40]),
+        ],
+        [255, 255, 255],
+    );
+
+    let mut animation = Animation::new(5);
+    let frame = |grid: &SandGrid| {
+        let map = grid.to_map();
+        Frame::from_map(&map, &palette, map.bounds, 4)
+    };
+    animation.push(frame(&grid));
+    let mut grains = 0;
+    grid.fill_with(|grid| {
+        grains += 1;
+        if grains % every == 0 {
+            animation.push(frame(grid));
+        }
+    });
+    animation.push(frame(&grid));
+    animation.save(&output).expect("Cannot write animation");
+    println!("Written {} frames to {}", animation.frames.len(), output);
+}
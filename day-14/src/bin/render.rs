@@ -1,11 +1,41 @@
 use bevy::prelude::*;
 // use bevy_flycam::PlayerPlugin;
-use day_14::render::VisualizationPlugin;
+use day_14::render::{run_headless, SandMode, VisualizationConfig, VisualizationPlugin};
 
+/// Usage: `cargo run -p day-14 --bin render -- [input] [--floor] [--speed N] [--headless]`
 fn main() {
+    let mut path = None;
+    let mut mode = SandMode::Floorless;
+    let mut speed = None;
+    let mut headless = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--floor" => mode = SandMode::WithFloor,
+            "--headless" => headless = true,
+            "--speed" => speed = args.next().map(|s| s.parse().expect("Invalid speed")),
+            _ => path = Some(arg),
+        }
+    }
+
+    let input = match path {
+        Some(path) => std::fs::read_to_string(path).expect("Cannot read input"),
+        None => include_str!("../../test.txt").to_string(),
+    };
+    let mut config = VisualizationConfig::new(&input, mode);
+    if let Some(speed) = speed {
+        config.speed = speed;
+    }
+
+    if headless {
+        println!("Resting sand: {}", run_headless(config));
+        return;
+    }
+
     App::new()
         .add_plugins(DefaultPlugins)
         // .add_plugin(PlayerPlugin)
-        .add_plugin(VisualizationPlugin)
+        .add_plugin(VisualizationPlugin { config })
         .run();
 }
@@ -1,3 +1,4 @@
 pub mod part1;
 pub mod part2;
 pub mod render;
+pub mod sand;
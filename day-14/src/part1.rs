@@ -1,5 +1,9 @@
 use std::collections::HashMap;
 
+use toolkit::map::{self, Bounds, Map, Tile};
+
+use crate::sand::{SandGrid, SOURCE};
+
 #[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct Pos {
     pub x: usize,
@@ -22,11 +26,15 @@ impl Pos {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Tile, Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Mat {
+    #[tile('#', color = "white")]
     Rock,
+    #[tile('o', color = "yellow")]
     Sand,
+    #[tile('.')]
     Air,
+    #[tile('+', color = "red")]
     Source,
 }
 
@@ -59,6 +67,19 @@ impl Scan {
         *self.map.get(pos).unwrap_or(&Mat::Air)
     }
 
+    /// The scanned rock, over the scan bounds
+    pub fn to_map(&self) -> Map<Mat> {
+        let mut map = Map::new();
+        for (pos, mat) in self.map.iter() {
+            map.tiles.insert(map::Pos::new(pos.x, pos.y), *mat);
+        }
+        map.bounds = Bounds::new(
+            map::Pos::new(self.min.x, self.min.y),
+            map::Pos::new(self.max.x, self.max.y),
+        );
+        map
+    }
+
     pub fn print(&self) {
         println!("Map (min={:?}) (max={:?}):", self.min, self.max);
         for y in self.min.y..=self.max.y {
@@ -109,31 +130,11 @@ pub fn scan_rock_paths(input: &str) -> Scan {
     scan
 }
 
-fn emit_sand(scan: &mut Scan) -> Option<Pos> {
-    let mut pos = Pos { x: 500, y: 0 };
-    while pos.y <= scan.max.y {
-        if scan.get(&pos.to(0, 1)) == Mat::Air {
-            pos = pos.to(0, 1);
-        } else if scan.get(&pos.to(-1, 1)) == Mat::Air {
-            pos = pos.to(-1, 1);
-        } else if scan.get(&pos.to(1, 1)) == Mat::Air {
-            pos = pos.to(1, 1);
-        } else {
-            scan.insert(pos, Mat::Sand);
-            return Some(pos);
-        }
-    }
-    None
-}
-
 pub fn count_sands_before_rest(input: &str) -> usize {
-    let mut scan = scan_rock_paths(input);
-    let mut count = 0;
-    while emit_sand(&mut scan).is_some() {
-        count += 1;
-    }
-    scan.print();
-    count
+    let scan = scan_rock_paths(input);
+    SandGrid::new(&scan, false, &[SOURCE])
+        .unwrap_or_else(|e| panic!("{}", e))
+        .fill()
 }
 
 #[cfg(test)]
@@ -148,6 +149,10 @@ pub mod tests {
         assert_eq!(scan.map.len(), 20);
         assert_eq!(scan.min, Pos { x: 494, y: 0 });
         assert_eq!(scan.max, Pos { x: 503, y: 9 });
+        assert_eq!(
+            scan.to_map().to_tiles('.').lines().last(),
+            Some("#########.")
+        );
     }
 
     #[test]
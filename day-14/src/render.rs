@@ -1,17 +1,130 @@
-use std::time::Duration;
-
 use bevy::prelude::*;
-use iyes_loopless::prelude::*;
 
-use crate::part1::{scan_rock_paths, Mat, Pos, Scan};
+use crate::{
+    part1::{scan_rock_paths, Mat, Pos, Scan},
+    sand::SOURCE,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandMode {
+    /// Sand falls into the abyss below the lowest rock (part 1)
+    Floorless,
+    /// An infinite floor lies two units below the lowest rock (part 2)
+    WithFloor,
+}
+
+/**
+ * Visualization settings, usually read from the command line.
+ */
+#[derive(Resource, Debug, Clone)]
+pub struct VisualizationConfig {
+    /// Scan of the rock paths, in the puzzle input format
+    pub input: String,
+    pub mode: SandMode,
+    /// Sand moves per second
+    pub speed: f32,
+}
+
+impl VisualizationConfig {
+    pub fn new(input: &str, mode: SandMode) -> Self {
+        Self {
+            input: input.to_string(),
+            mode,
+            speed: 4.0,
+        }
+    }
+}
+
+/**
+ * Controls the simulation pace. When `fixed_steps` is set, every update
+ * runs that many sand moves regardless of the elapsed time, which makes the
+ * simulation deterministic in headless mode.
+ */
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Playback {
+    pub paused: bool,
+    pub speed: f32,
+    pub step_once: bool,
+    pub fixed_steps: Option<usize>,
+    budget: f32,
+}
+
+impl Playback {
+    fn steps(&mut self, delta_seconds: f32) -> usize {
+        if let Some(steps) = self.fixed_steps {
+            return steps;
+        }
+        if self.paused {
+            let steps = self.step_once as usize;
+            self.step_once = false;
+            return steps;
+        }
+        self.budget += delta_seconds * self.speed;
+        let steps = self.budget.floor();
+        self.budget -= steps;
+        steps as usize
+    }
+}
+
+#[derive(Resource)]
+pub struct ScanResource {
+    pub scan: Scan,
+    pub current: Option<Pos>,
+    pub finished: bool,
+    pub resting: usize,
+}
+
+/// Sent when a sand unit comes to rest
+pub struct SandStopped(pub Pos);
+
+/**
+ * Runs the sand simulation without rendering anything, so it can be used
+ * with `MinimalPlugins` only.
+ */
+pub struct SimulationPlugin {
+    pub config: VisualizationConfig,
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        let mut scan = scan_rock_paths(&self.config.input);
+        if self.config.mode == SandMode::WithFloor {
+            scan.floor = scan.max.y + 2;
+        }
+
+        app.insert_resource(self.config.clone())
+            .insert_resource(ScanResource {
+                scan,
+                current: None,
+                finished: false,
+                resting: 0,
+            })
+            .insert_resource(Playback {
+                speed: self.config.speed,
+                ..default()
+            })
+            .add_event::<SandStopped>()
+            .add_system(advance_sand);
+    }
+}
 
-pub struct VisualizationPlugin;
+/**
+ * Renders the sand simulation in 3D, with keyboard controls:
+ * space pauses, right arrow moves by a single step while paused,
+ * up/down arrows (or +/-) change speed.
+ */
+pub struct VisualizationPlugin {
+    pub config: VisualizationConfig,
+}
 
 impl Plugin for VisualizationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_fixed_timestep(Duration::from_millis(250), "animate_sand")
-            .add_startup_system(load_scan_map)
-            .add_fixed_timestep_system("animate_sand", 0, animate_sand);
+        app.add_plugin(SimulationPlugin {
+            config: self.config.clone(),
+        })
+        .add_startup_system(load_scan_map)
+        .add_system(control_playback)
+        .add_system(draw_sand.after(advance_sand));
     }
 }
 
@@ -24,6 +137,12 @@ struct Block(Mat);
 #[derive(Component)]
 struct FallingParticle;
 
+#[derive(Resource)]
+struct SandAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
 #[derive(Bundle)]
 struct BlockBundle {
     position: Position,
@@ -32,35 +151,96 @@ struct BlockBundle {
     object: PbrBundle,
 }
 
+/// Horizontal extent of the scene, including the floor where sand can pile
+/// up to `floor` units on each side of the source.
+fn scene_bounds(scan: &Scan) -> (Pos, Pos) {
+    let (mut min, mut max) = (scan.min, scan.max);
+    if scan.floor > 0 {
+        min.x = min.x.min(SOURCE.x.saturating_sub(scan.floor));
+        max.x = max.x.max(SOURCE.x + scan.floor);
+        max.y = scan.floor;
+    }
+    (min, max)
+}
+
+/// Places the camera in front of the scene, far enough to see all of it
+/// with the default field of view.
+pub fn frame_camera(min: Pos, max: Pos, aspect_ratio: f32) -> Transform {
+    let center = Vec3::new(
+        (min.x + max.x) as f32 / 2.0,
+        -((min.y + max.y) as f32) / 2.0,
+        0.0,
+    );
+    let width = (max.x - min.x + 1) as f32;
+    let height = (max.y - min.y + 1) as f32;
+    let half_fov = PerspectiveProjection::default().fov / 2.0;
+    // Some margin around the scene
+    let extent = height.max(width / aspect_ratio) * 1.1;
+    let distance = extent / 2.0 / half_fov.tan();
+
+    Transform::from_translation(center + Vec3::Z * distance).looking_at(center, Vec3::Y)
+}
+
 fn load_scan_map(
     mut commands: Commands,
+    scan: Res<ScanResource>,
+    windows: Res<Windows>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let input = include_str!("../test.txt");
-    let scan = scan_rock_paths(input);
+    let scan = &scan.scan;
+    let cube = meshes.add(Mesh::from(shape::Cube { size: 1.0 }));
+    let rock = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.8, 0.8, 0.8),
+        ..default()
+    });
 
     scan.map.iter().for_each(|(pos, mat)| {
         commands.spawn(BlockBundle {
             position: Position(*pos),
             block: Block(*mat),
             object: PbrBundle {
-                mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
-                material: materials.add(StandardMaterial {
-                    base_color: Color::rgb(0.8, 0.8, 0.8),
-                    ..default()
-                }),
+                mesh: cube.clone(),
+                material: rock.clone(),
                 transform: Transform::from_xyz(pos.x as f32, -(pos.y as f32), 0.0),
                 ..default()
             },
         });
     });
 
+    let (min, max) = scene_bounds(scan);
+    if scan.floor > 0 {
+        let width = (max.x - min.x + 1) as f32;
+        commands.spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(width, 1.0, 1.0))),
+            material: rock,
+            transform: Transform::from_xyz((min.x + max.x) as f32 / 2.0, -(scan.floor as f32), 0.0),
+            ..default()
+        });
+    }
+
+    commands.insert_resource(SandAssets {
+        mesh: meshes.add(Mesh::from(shape::UVSphere {
+            radius: 0.5,
+            ..default()
+        })),
+        material: materials.add(StandardMaterial {
+            base_color: Color::hex("C2B280").unwrap(),
+            ..default()
+        }),
+    });
+
+    let aspect_ratio = windows
+        .get_primary()
+        .map(|w| w.width() / w.height())
+        .unwrap_or(16.0 / 9.0);
+    let camera = frame_camera(min, max, aspect_ratio);
+
     commands.spawn(PointLightBundle {
-        transform: Transform::from_xyz(500.0, -2.0, 50.0),
+        transform: Transform::from_translation(camera.translation),
         point_light: PointLight {
             intensity: 50000.0,
-            range: 200.0,
+            range: camera.translation.z * 4.0,
             radius: 0.2,
             shadows_enabled: true,
             ..default()
@@ -68,98 +248,183 @@ fn load_scan_map(
         ..default()
     });
     commands.spawn(Camera3dBundle {
-        transform: Transform::from_xyz(500.0, -4.0, 20.0)
-            .looking_at(Vec3::new(500.0, -4.0, 0.0), Vec3::Y),
+        transform: camera,
         ..default()
     });
-
-    commands.insert_resource(ScanResource {
-        scan,
-        current: None,
-    });
-}
-
-#[derive(Resource)]
-struct ScanResource {
-    scan: Scan,
-    current: Option<Pos>,
 }
 
 enum SandState {
     Falling(Pos),
-    Stopped,
+    Stopped(Pos),
     Finished,
 }
 
+fn scan_get(scan: &Scan, pos: &Pos) -> Mat {
+    match scan.floor > 0 && pos.y == scan.floor {
+        true => Mat::Rock,
+        false => scan.get(pos),
+    }
+}
+
 fn next_sand_particle(scan: &mut Scan, pos: Option<Pos>) -> SandState {
-    let mut pos = match pos {
+    let pos = match pos {
         Some(p) => p,
-        None => Pos { x: 500, y: 0 },
+        None if scan.get(&SOURCE) != Mat::Air => return SandState::Finished,
+        None => SOURCE,
     };
 
-    if pos.y > scan.max.y {
+    if scan.floor == 0 && pos.y > scan.max.y {
         return SandState::Finished;
     }
 
-    if scan.get(&pos.to(0, 1)) == Mat::Air {
-        pos = pos.to(0, 1);
-        SandState::Falling(pos)
-    } else if scan.get(&pos.to(-1, 1)) == Mat::Air {
-        pos = pos.to(-1, 1);
-        SandState::Falling(pos)
-    } else if scan.get(&pos.to(1, 1)) == Mat::Air {
-        pos = pos.to(1, 1);
-        SandState::Falling(pos)
+    if scan_get(scan, &pos.to(0, 1)) == Mat::Air {
+        SandState::Falling(pos.to(0, 1))
+    } else if scan_get(scan, &pos.to(-1, 1)) == Mat::Air {
+        SandState::Falling(pos.to(-1, 1))
+    } else if scan_get(scan, &pos.to(1, 1)) == Mat::Air {
+        SandState::Falling(pos.to(1, 1))
     } else {
         scan.insert(pos, Mat::Sand);
-        SandState::Stopped
+        SandState::Stopped(pos)
     }
 }
 
-fn animate_sand(
+fn advance_sand(
+    time: Res<Time>,
+    mut playback: ResMut<Playback>,
     mut scan: ResMut<ScanResource>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut query: Query<(Entity, &mut Transform), With<FallingParticle>>,
+    mut stopped: EventWriter<SandStopped>,
 ) {
-    let current_pos = scan.current;
-    let falling_particle = query.get_single_mut();
-
-    match next_sand_particle(&mut scan.scan, current_pos) {
-        SandState::Falling(pos) => {
-            match falling_particle {
-                Ok((_, mut transform)) => {
-                    transform.translation = Vec3::new(pos.x as f32, -(pos.y as f32), 0.0);
-                }
-                Err(_) => {
-                    commands.spawn((
-                        FallingParticle,
-                        PbrBundle {
-                            mesh: meshes.add(Mesh::from(shape::UVSphere {
-                                radius: 0.5,
-                                ..default()
-                            })),
-                            material: materials.add(StandardMaterial {
-                                base_color: Color::hex("C2B280").unwrap(),
-                                ..default()
-                            }),
-                            transform: Transform::from_xyz(pos.x as f32, -(pos.y as f32), 0.0),
-                            ..default()
-                        },
-                    ));
-                }
-            }
-            scan.current = Some(pos);
+    let steps = playback.steps(time.delta_seconds());
+    for _ in 0..steps {
+        if scan.finished {
+            return;
         }
-        SandState::Stopped => {
-            scan.current = None;
-            commands
-                .entity(falling_particle.unwrap().0)
-                .remove::<FallingParticle>();
+
+        let current = scan.current;
+        match next_sand_particle(&mut scan.scan, current) {
+            SandState::Falling(pos) => scan.current = Some(pos),
+            SandState::Stopped(pos) => {
+                scan.current = None;
+                scan.resting += 1;
+                stopped.send(SandStopped(pos));
+            }
+            SandState::Finished => {
+                scan.current = None;
+                scan.finished = true;
+            }
         }
-        SandState::Finished => {
-            scan.current = None;
+    }
+}
+
+fn control_playback(keys: Res<Input<KeyCode>>, mut playback: ResMut<Playback>) {
+    if keys.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+    }
+    if keys.just_pressed(KeyCode::Right) {
+        playback.step_once = true;
+    }
+    if keys.any_just_pressed([KeyCode::Up, KeyCode::Plus, KeyCode::Equals]) {
+        playback.speed *= 2.0;
+    }
+    if keys.any_just_pressed([KeyCode::Down, KeyCode::Minus]) {
+        playback.speed = (playback.speed / 2.0).max(0.5);
+    }
+}
+
+fn draw_sand(
+    scan: Res<ScanResource>,
+    sand: Res<SandAssets>,
+    mut stopped: EventReader<SandStopped>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform), With<FallingParticle>>,
+) {
+    let translation = |pos: Pos| Vec3::new(pos.x as f32, -(pos.y as f32), 0.0);
+
+    for SandStopped(pos) in stopped.iter() {
+        commands.spawn((
+            Position(*pos),
+            Block(Mat::Sand),
+            PbrBundle {
+                mesh: sand.mesh.clone(),
+                material: sand.material.clone(),
+                transform: Transform::from_translation(translation(*pos)),
+                ..default()
+            },
+        ));
+    }
+
+    match (scan.current, query.get_single_mut()) {
+        (Some(pos), Ok((_, mut transform))) => transform.translation = translation(pos),
+        (Some(pos), Err(_)) => {
+            commands.spawn((
+                FallingParticle,
+                PbrBundle {
+                    mesh: sand.mesh.clone(),
+                    material: sand.material.clone(),
+                    transform: Transform::from_translation(translation(pos)),
+                    ..default()
+                },
+            ));
         }
+        (None, Ok((entity, _))) => commands.entity(entity).despawn(),
+        (None, Err(_)) => {}
+    }
+}
+
+/// Runs the simulation with no window until all the sand has settled,
+/// returning the number of resting sand units.
+pub fn run_headless(config: VisualizationConfig) -> usize {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(SimulationPlugin { config });
+    app.world.resource_mut::<Playback>().fixed_steps = Some(1_000);
+
+    while !app.world.resource::<ScanResource>().finished {
+        app.update();
+    }
+
+    app.world.resource::<ScanResource>().resting
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::render::*;
+
+    #[test]
+    fn test_headless() {
+        let input = include_str!("../test.txt");
+        assert_eq!(
+            run_headless(VisualizationConfig::new(input, SandMode::Floorless)),
+            24
+        );
+        assert_eq!(
+            run_headless(VisualizationConfig::new(input, SandMode::WithFloor)),
+            93
+        );
+    }
+
+    #[test]
+    fn test_frame_camera() {
+        let transform = frame_camera(Pos { x: 494, y: 0 }, Pos { x: 504, y: 10 }, 1.0);
+        assert_eq!(transform.translation.x, 499.0);
+        assert_eq!(transform.translation.y, -5.0);
+        assert!(transform.translation.z > 11.0);
+    }
+
+    #[test]
+    fn test_playback() {
+        let mut playback = Playback {
+            speed: 4.0,
+            ..Default::default()
+        };
+        assert_eq!(playback.steps(0.1), 0);
+        assert_eq!(playback.steps(0.2), 1);
+
+        playback.paused = true;
+        assert_eq!(playback.steps(1.0), 0);
+        playback.step_once = true;
+        assert_eq!(playback.steps(1.0), 1);
+        assert_eq!(playback.steps(1.0), 0);
     }
 }
@@ -0,0 +1,276 @@
+use std::{error::Error, fmt};
+
+use toolkit::map::{self, Bounds, Map};
+
+use crate::part1::{Mat, Pos, Scan};
+
+pub const SOURCE: Pos = Pos { x: 500, y: 0 };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandError {
+    /// With a floor, grains from this source would never come to rest
+    SourceBelowFloor(Pos),
+    /// Grains from this source could spread left of `x = 0`
+    SourceTooFarLeft(Pos),
+}
+
+impl fmt::Display for SandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SandError::SourceBelowFloor(p) => {
+                write!(f, "source {},{} is below the floor", p.x, p.y)
+            }
+            SandError::SourceTooFarLeft(p) => {
+                write!(
+                    f,
+                    "sand from source {},{} would spread below x = 0",
+                    p.x, p.y
+                )
+            }
+        }
+    }
+}
+
+impl Error for SandError {}
+
+/**
+ * A dense sand simulator. Each source keeps the path of its last grain as a
+ * stack: the next grain follows the same path up to the position before the
+ * one where the previous grain came to rest, so it resumes from there
+ * instead of falling from the source again.
+ */
+#[derive(Debug, Clone)]
+pub struct SandGrid {
+    cells: Vec<Mat>,
+    sources: Vec<Pos>,
+    min_x: usize,
+    width: usize,
+    height: usize,
+    /// Lowest rock, grains falling below it go into the abyss (without floor)
+    max_y: usize,
+    has_floor: bool,
+}
+
+impl SandGrid {
+    /// Builds a grid covering the scan and every cell the sources could
+    /// reach, with the floor two units below the lowest rock if requested.
+    pub fn new(scan: &Scan, has_floor: bool, sources: &[Pos]) -> Result<Self, SandError> {
+        let max_y = scan.max.y;
+        let floor_y = max_y + 2;
+        let height = floor_y + 1;
+        // Sand spreads at most one column per row, down to the floor (or the
+        // abyss just above it)
+        let spread = |s: &Pos| floor_y.saturating_sub(s.y);
+        for source in sources.iter() {
+            if has_floor && source.y >= floor_y {
+                return Err(SandError::SourceBelowFloor(*source));
+            }
+            if source.x < spread(source) {
+                return Err(SandError::SourceTooFarLeft(*source));
+            }
+        }
+        let min_x = sources
+            .iter()
+            .map(|s| s.x - spread(s))
+            .fold(scan.min.x, usize::min);
+        let max_x = sources
+            .iter()
+            .map(|s| s.x + spread(s))
+            .fold(scan.max.x, usize::max);
+        let width = max_x - min_x + 1;
+
+        let mut grid = Self {
+            cells: vec![Mat::Air; width * height],
+            sources: sources.to_vec(),
+            min_x,
+            width,
+            height,
+            max_y,
+            has_floor,
+        };
+        for (pos, mat) in scan.map.iter() {
+            let index = grid.index(*pos);
+            grid.cells[index] = *mat;
+        }
+        if has_floor {
+            let floor = floor_y * width;
+            grid.cells[floor..].fill(Mat::Rock);
+        }
+        Ok(grid)
+    }
+
+    pub fn sources(&self) -> &[Pos] {
+        &self.sources
+    }
+
+    fn index(&self, pos: Pos) -> usize {
+        pos.y * self.width + (pos.x - self.min_x)
+    }
+
+    pub fn get(&self, pos: Pos) -> Mat {
+        if pos.y >= self.height || pos.x < self.min_x || pos.x >= self.min_x + self.width {
+            return Mat::Air;
+        }
+        self.cells[self.index(pos)]
+    }
+
+    /// Next position of a grain, `None` when it comes to rest
+    fn fall(&self, pos: Pos) -> Option<Pos> {
+        [pos.to(0, 1), pos.to(-1, 1), pos.to(1, 1)]
+            .into_iter()
+            .find(|p| self.get(*p) == Mat::Air)
+    }
+
+    /// Drops a grain following `path`, leaving in it the path for the next
+    /// one. Returns where the grain rested, or `None` if it reached the abyss.
+    fn drop_grain(&mut self, path: &mut Vec<Pos>) -> Option<Pos> {
+        while let Some(&pos) = path.last() {
+            if !self.has_floor && pos.y > self.max_y {
+                return None;
+            }
+            match self.fall(pos) {
+                Some(next) => path.push(next),
+                None => {
+                    path.pop();
+                    let index = self.index(pos);
+                    self.cells[index] = Mat::Sand;
+                    return Some(pos);
+                }
+            }
+        }
+        None
+    }
+
+    /// Emits sand from every source in turn, one grain at a time, returning
+    /// the number of resting grains. A source stops when a grain falls into
+    /// the abyss or when the source itself gets covered by sand (or rock).
+    pub fn fill(&mut self) -> usize {
+        self.fill_with(|_| {})
+    }
+
+    /// Like `fill`, calling `rested` with the grid after every resting grain
+    pub fn fill_with(&mut self, mut rested: impl FnMut(&Self)) -> usize {
+        let mut paths: Vec<Vec<Pos>> = self.sources.iter().map(|s| vec![*s]).collect();
+        let mut active = vec![true; paths.len()];
+        let mut count = 0;
+
+        while active.iter().any(|a| *a) {
+            for i in 0..paths.len() {
+                if !active[i] {
+                    continue;
+                }
+                // Another source may have filled a cell of this path, which
+                // is then valid only up to that cell
+                let path = &mut paths[i];
+                if let Some(filled) = path.iter().position(|p| self.get(*p) != Mat::Air) {
+                    path.truncate(filled);
+                }
+                if paths[i].is_empty() {
+                    active[i] = false;
+                    continue;
+                }
+
+                match self.drop_grain(&mut paths[i]) {
+                    Some(_) => {
+                        count += 1;
+                        rested(self);
+                    }
+                    None => active[i] = false,
+                }
+            }
+        }
+
+        count
+    }
+
+    /// The rock, sand and uncovered sources, with bounds covering the whole
+    /// grid so that snapshots can be animated
+    pub fn to_map(&self) -> Map<Mat> {
+        let mut map = Map::new();
+        for (i, mat) in self.cells.iter().enumerate() {
+            if *mat != Mat::Air {
+                let pos = map::Pos::new(self.min_x + i % self.width, i / self.width);
+                map.tiles.insert(pos, *mat);
+            }
+        }
+        for source in self.sources.iter() {
+            let pos = map::Pos::new(source.x, source.y);
+            map.tiles.entry(pos).or_insert(Mat::Source);
+        }
+        map.bounds = Bounds::new(
+            map::Pos::new(self.min_x, 0),
+            map::Pos::new(self.min_x + self.width - 1, self.height - 1),
+        );
+        map
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{part1::scan_rock_paths, sand::*};
+
+    #[test]
+    fn test_single_source() {
+        let scan = scan_rock_paths(include_str!("../test.txt"));
+        let grid = |has_floor| SandGrid::new(&scan, has_floor, &[SOURCE]).unwrap();
+        assert_eq!(grid(false).fill(), 24);
+        assert_eq!(grid(true).fill(), 93);
+    }
+
+    #[test]
+    fn test_custom_sources() {
+        let scan = scan_rock_paths(include_str!("../test.txt"));
+
+        // A source right above the left edge of the rock pours into the abyss
+        let source = Pos { x: 494, y: 0 };
+        let mut grid = SandGrid::new(&scan, false, &[source]).unwrap();
+        assert_eq!(grid.fill(), 0);
+
+        // Two sources over a single rock, with the floor at y = 4. Their
+        // piles merge and both sources end up covered:
+        //
+        // ....+.+...      ....o.o...
+        // ..........      ...ooooo..
+        // ....#.....  ->  ..oo#oooo.
+        // ..........      .ooooooooo
+        let scan = scan_rock_paths("4,2 -> 4,2");
+        let sources = [Pos { x: 4, y: 0 }, Pos { x: 6, y: 0 }];
+        let mut grid = SandGrid::new(&scan, true, &sources).unwrap();
+        assert_eq!(grid.fill(), 22);
+        assert_eq!(grid.get(sources[0]), Mat::Sand);
+        assert_eq!(grid.get(sources[1]), Mat::Sand);
+        assert_eq!(grid.get(Pos { x: 9, y: 3 }), Mat::Sand);
+        assert_eq!(grid.get(Pos { x: 0, y: 3 }), Mat::Air);
+    }
+
+    #[test]
+    fn test_to_map() {
+        // A single grain rests on the ledge, the next one slides off it
+        let scan = scan_rock_paths("499,2 -> 501,2");
+        let mut grid = SandGrid::new(&scan, false, &[SOURCE]).unwrap();
+        let mut snapshots = vec![];
+        assert_eq!(grid.fill_with(|g| snapshots.push(g.to_map())), 1);
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(
+            snapshots[0].to_tiles('.'),
+            "....+....\n....o....\n...###...\n.........\n........."
+        );
+    }
+
+    #[test]
+    fn test_invalid_sources() {
+        let scan = scan_rock_paths(include_str!("../test.txt"));
+        let below = Pos { x: 500, y: 11 };
+        assert_eq!(
+            SandGrid::new(&scan, true, &[below]).unwrap_err(),
+            SandError::SourceBelowFloor(below)
+        );
+        assert!(SandGrid::new(&scan, false, &[below]).is_ok());
+
+        let left = Pos { x: 3, y: 0 };
+        assert_eq!(
+            SandGrid::new(&scan, true, &[SOURCE, left]).unwrap_err(),
+            SandError::SourceTooFarLeft(left)
+        );
+    }
+}
@@ -0,0 +1,378 @@
+use itertools::Itertools;
+
+use crate::part1::{Pos, Scanner};
+
+/// Far enough to be outside any scanner, while keeping products in `i64`
+const INFINITY: i64 = 1 << 40;
+
+/// An inclusive rectangle of positions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub min: Pos,
+    pub max: Pos,
+}
+
+impl Rect {
+    pub fn new(min: Pos, max: Pos) -> Self {
+        Self { min, max }
+    }
+
+    pub fn area(&self) -> i64 {
+        (self.max.x - self.min.x + 1) as i64 * (self.max.y - self.min.y + 1) as i64
+    }
+}
+
+/// A linear function `slope * x + offset`
+#[derive(Clone, Copy, Debug)]
+struct Line {
+    slope: i64,
+    offset: i64,
+}
+
+impl Line {
+    fn at(&self, x: i64) -> i64 {
+        self.slope * x + self.offset
+    }
+
+    /// Integer positions around the crossing with `other`, if any
+    fn crossings(&self, other: &Line) -> Option<[i64; 2]> {
+        let (num, den) = (other.offset - self.offset, self.slope - other.slope);
+        if den == 0 {
+            return None;
+        }
+        let (num, den) = if den < 0 { (-num, -den) } else { (num, den) };
+        let floor = num.div_euclid(den);
+        Some([floor, floor + 1])
+    }
+}
+
+/// Cells `(x, y)` with `x` in the given range and `max(lowers) <= y <= min(uppers)`
+struct Region {
+    x: (i64, i64),
+    lowers: [Line; 3],
+    uppers: [Line; 3],
+}
+
+impl Region {
+    fn bounds_at(&self, x: i64) -> (i64, i64) {
+        let low = self.lowers.iter().map(|l| l.at(x)).max().unwrap();
+        let high = self.uppers.iter().map(|l| l.at(x)).min().unwrap();
+        (low, high)
+    }
+
+    fn width_at(&self, x: i64) -> i64 {
+        let (low, high) = self.bounds_at(x);
+        high - low + 1
+    }
+
+    /// Splits the x range where any pair of bounds cross, so that on every
+    /// piece the column height is linear and never changes sign.
+    fn pieces(&self) -> Vec<(i64, i64)> {
+        let (start, end) = self.x;
+        if start > end {
+            return vec![];
+        }
+
+        let mut points = vec![start];
+        let pairs = self
+            .lowers
+            .iter()
+            .tuple_combinations()
+            .chain(self.uppers.iter().tuple_combinations());
+        for (a, b) in pairs {
+            points.extend(a.crossings(b).into_iter().flatten());
+        }
+        for (upper, lower) in self.uppers.iter().cartesian_product(self.lowers.iter()) {
+            // Where the column height (upper - lower + 1) reaches zero
+            let shifted = Line {
+                slope: upper.slope,
+                offset: upper.offset + 1,
+            };
+            points.extend(shifted.crossings(lower).into_iter().flatten());
+        }
+
+        let points = points
+            .into_iter()
+            .filter(|&p| p >= start && p <= end)
+            .sorted()
+            .dedup()
+            .collect_vec();
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (p, points.get(i + 1).map(|n| n - 1).unwrap_or(end)))
+            .collect()
+    }
+
+    fn count(&self) -> i64 {
+        self.pieces()
+            .into_iter()
+            .map(|(a, b)| {
+                let (wa, wb) = (self.width_at(a), self.width_at(b));
+                match (wa >= 0, wb >= 0) {
+                    (true, true) => (wa + wb) * (b - a + 1) / 2,
+                    (false, false) => 0,
+                    _ => (a..=b).map(|x| self.width_at(x).max(0)).sum(),
+                }
+            })
+            .sum()
+    }
+
+    fn cells(self) -> impl Iterator<Item = Pos> {
+        let (start, end) = self.x;
+        (start..=end).flat_map(move |x| {
+            let (low, high) = self.bounds_at(x);
+            (low..=high).map(move |y| Pos {
+                x: x as i32,
+                y: y as i32,
+            })
+        })
+    }
+}
+
+/**
+ * Area covered by the scanners. Rotating coordinates by 45° (`u = x + y`,
+ * `v = x - y`) turns every scanner area into an axis-aligned square, so the
+ * plane can be split by all the square edges into a small grid of blocks,
+ * each one either fully covered or fully uncovered.
+ */
+pub struct Coverage {
+    us: Vec<i64>,
+    vs: Vec<i64>,
+    /// Whether each block `(i, j)` is covered, indexed as `i * (vs.len() - 1) + j`
+    covered: Vec<bool>,
+    bounds: Rect,
+}
+
+impl Coverage {
+    pub fn new(scanners: &[Scanner]) -> Self {
+        // Half-open ranges of each square: [u0, u1) x [v0, v1)
+        let squares = scanners
+            .iter()
+            .map(|s| {
+                let (u, v) = (
+                    (s.center.x + s.center.y) as i64,
+                    (s.center.x - s.center.y) as i64,
+                );
+                let r = s.radius as i64;
+                (u - r, u + r + 1, v - r, v + r + 1)
+            })
+            .collect_vec();
+
+        let edges = |ranges: Vec<(i64, i64)>| {
+            ranges
+                .into_iter()
+                .flat_map(|(a, b)| [a, b])
+                .chain([-INFINITY, INFINITY])
+                .sorted()
+                .dedup()
+                .collect_vec()
+        };
+        let us = edges(squares.iter().map(|s| (s.0, s.1)).collect());
+        let vs = edges(squares.iter().map(|s| (s.2, s.3)).collect());
+
+        let covered = us
+            .iter()
+            .tuple_windows()
+            .cartesian_product(vs.iter().tuple_windows())
+            .map(|((&u, _), (&v, _))| {
+                squares
+                    .iter()
+                    .any(|s| s.0 <= u && u < s.1 && s.2 <= v && v < s.3)
+            })
+            .collect_vec();
+
+        let bounds = Rect::new(
+            Pos {
+                x: scanners
+                    .iter()
+                    .map(|s| s.center.x - s.radius)
+                    .min()
+                    .unwrap_or(0),
+                y: scanners
+                    .iter()
+                    .map(|s| s.center.y - s.radius)
+                    .min()
+                    .unwrap_or(0),
+            },
+            Pos {
+                x: scanners
+                    .iter()
+                    .map(|s| s.center.x + s.radius)
+                    .max()
+                    .unwrap_or(0),
+                y: scanners
+                    .iter()
+                    .map(|s| s.center.y + s.radius)
+                    .max()
+                    .unwrap_or(0),
+            },
+        );
+
+        Self {
+            us,
+            vs,
+            covered,
+            bounds,
+        }
+    }
+
+    /// Smallest rectangle containing every covered position
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Blocks intersected with `rect`, with their covered state
+    fn regions(&self, rect: Rect) -> impl Iterator<Item = (Region, bool)> + '_ {
+        let (x0, y0) = (rect.min.x as i64, rect.min.y as i64);
+        let (x1, y1) = (rect.max.x as i64, rect.max.y as i64);
+
+        self.us
+            .iter()
+            .tuple_windows()
+            .cartesian_product(self.vs.iter().tuple_windows())
+            .zip(self.covered.iter())
+            .map(move |(((&u0, &u1), (&v0, &v1)), &covered)| {
+                // x = (u + v) / 2 and y = (u - v) / 2, with u0 <= u < u1 and v0 <= v < v1
+                let region = Region {
+                    x: (
+                        x0.max((u0 + v0 + 1).div_euclid(2)),
+                        x1.min((u1 + v1 - 2).div_euclid(2)),
+                    ),
+                    lowers: [
+                        Line {
+                            slope: 0,
+                            offset: y0,
+                        },
+                        Line {
+                            slope: -1,
+                            offset: u0,
+                        },
+                        Line {
+                            slope: 1,
+                            offset: -(v1 - 1),
+                        },
+                    ],
+                    uppers: [
+                        Line {
+                            slope: 0,
+                            offset: y1,
+                        },
+                        Line {
+                            slope: -1,
+                            offset: u1 - 1,
+                        },
+                        Line {
+                            slope: 1,
+                            offset: -v0,
+                        },
+                    ],
+                };
+                (region, covered)
+            })
+    }
+
+    pub fn uncovered_count(&self, rect: Rect) -> i64 {
+        self.regions(rect)
+            .filter(|(_, covered)| !covered)
+            .map(|(region, _)| region.count())
+            .sum()
+    }
+
+    pub fn covered_count(&self, rect: Rect) -> i64 {
+        rect.area() - self.uncovered_count(rect)
+    }
+
+    /// Total number of covered positions
+    pub fn covered_area(&self) -> i64 {
+        self.covered_count(self.bounds)
+    }
+
+    /// Every uncovered position inside `rect`, that is every position where
+    /// an undetected beacon could be. Positions are generated lazily, block
+    /// by block, so that a huge uncovered area doesn't have to fit in memory.
+    pub fn uncovered(&self, rect: Rect) -> impl Iterator<Item = Pos> + '_ {
+        self.regions(rect)
+            .filter(|(region, covered)| !covered && region.count() > 0)
+            .flat_map(|(region, _)| region.cells())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::coverage::*;
+
+    fn parse(input: &str) -> Vec<Scanner> {
+        input.lines().map(Scanner::parse).collect()
+    }
+
+    /// Counts by checking every position, for comparison
+    fn brute_force(scanners: &[Scanner], rect: Rect) -> i64 {
+        (rect.min.y..=rect.max.y)
+            .cartesian_product(rect.min.x..=rect.max.x)
+            .filter(|&(y, x)| {
+                scanners
+                    .iter()
+                    .any(|s| s.center.dist(&Pos { x, y }) <= s.radius)
+            })
+            .count() as i64
+    }
+
+    #[test]
+    fn test_single_scanner() {
+        let scanners = parse("Sensor at x=0, y=0: closest beacon is at x=2, y=0");
+        let coverage = Coverage::new(&scanners);
+        assert_eq!(coverage.covered_area(), 13);
+
+        let rect = Rect::new(Pos { x: 0, y: -5 }, Pos { x: 5, y: 5 });
+        assert_eq!(coverage.covered_count(rect), brute_force(&scanners, rect));
+    }
+
+    #[test]
+    fn test_example_counts() {
+        let scanners = parse(include_str!("../test.txt"));
+        let coverage = Coverage::new(&scanners);
+
+        let rect = Rect::new(Pos { x: -3, y: 5 }, Pos { x: 24, y: 22 });
+        assert_eq!(coverage.covered_count(rect), brute_force(&scanners, rect));
+        assert_eq!(
+            coverage.covered_area(),
+            brute_force(&scanners, coverage.bounds())
+        );
+    }
+
+    #[test]
+    fn test_uncovered() {
+        let scanners = parse(include_str!("../test.txt"));
+        let coverage = Coverage::new(&scanners);
+        let rect = Rect::new(Pos { x: 0, y: 0 }, Pos { x: 20, y: 20 });
+        assert_eq!(
+            coverage.uncovered(rect).collect_vec(),
+            vec![Pos { x: 14, y: 11 }]
+        );
+        assert_eq!(coverage.uncovered_count(rect), 1);
+
+        let larger = Rect::new(Pos { x: -2, y: -2 }, Pos { x: 22, y: 22 });
+        assert_eq!(
+            coverage.uncovered(larger).count() as i64,
+            larger.area() - brute_force(&scanners, larger)
+        );
+    }
+
+    #[test]
+    fn test_uncovered_lazily() {
+        let coverage = Coverage::new(&parse("Sensor at x=0, y=0: closest beacon is at x=1, y=0"));
+        // Billions of uncovered cells, only the first ones are generated
+        let huge = Rect::new(
+            Pos { x: 10, y: 0 },
+            Pos {
+                x: 70_000,
+                y: 70_000,
+            },
+        );
+        assert_eq!(coverage.uncovered_count(huge), huge.area());
+        let first = coverage.uncovered(huge).take(3).collect_vec();
+        assert_eq!(first.len(), 3);
+        assert!(first.iter().all(|p| p.x >= 10 && p.y >= 0));
+    }
+}
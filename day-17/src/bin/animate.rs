@@ -0,0 +1,42 @@
+use day_17::{
+    part1::{get_rocks, parse_flow},
+    sim::TetrisSim,
+};
+use toolkit::animation::{Animation, Frame, Palette};
+
+/// Rows of the chamber shown below the spawn point
+const ROWS: i64 = 40;
+
+/// Writes the falling rocks as an animation, usage:
+/// `cargo run -p day-17 --bin animate -- [input] [output.gif|output.png|dir] [rocks]`
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let input = match args.next() {
+        Some(path) => std::fs::read_to_string(path).expect("Cannot read input"),
+        None => include_str!("../../test.txt").to_string(),
+    };
+    let output = args.next().unwrap_or_else(|| "rocks.gif".to_string());
+    let rocks: usize = args
+        .next()
+        .map(|n| n.parse().expect("Invalid number of rocks"))
+        .unwrap_or(30);
+
+    let mut sim = TetrisSim::new(get_rocks(), parse_flow(&input), 7);
+    let tallest = sim.rocks.iter().map(|r| r.height).max().unwrap();
+    let palette = Palette::by_char(
+        [20, 20, 30],
+        &[('#', [150, 150, 160]), ('@', [220, 60, 40])],
+        [255, 255, 255],
+    );
+
+    let mut animation = Animation::new(4);
+    while sim.settled < rocks {
+        sim.step();
+        // The window follows the top of the chamber, with room for a new rock
+        let top = sim.height() + sim.spawn.y + tallest;
+        let map = sim.to_map(top, ROWS);
+        animation.push(Frame::from_map(&map, &palette, map.bounds, 8));
+    }
+    animation.save(&output).expect("Cannot write animation");
+    println!("Written {} frames to {}", animation.frames.len(), output);
+}
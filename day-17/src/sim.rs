@@ -0,0 +1,323 @@
+use colored::Colorize;
+use toolkit::map::{Bounds, Map, Pos, Tile};
+
+use crate::part1::{Flow, Rock};
+
+/// How deep `skyline` looks into each column
+pub const SKYLINE_DEPTH: i64 = 64;
+
+/// Where new rocks appear: `x` columns from the left wall and `y` rows above
+/// the highest rock (or the floor).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spawn {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Default for Spawn {
+    fn default() -> Self {
+        Self { x: 2, y: 3 }
+    }
+}
+
+/// A chamber cell in a `TetrisSim::to_map` snapshot
+#[derive(Tile, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    #[tile('#', color = "white")]
+    Settled,
+    #[tile('@', color = "red")]
+    Falling,
+}
+
+/// The rock currently falling, with the position of its bottom-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Falling {
+    pub rock: usize,
+    pub x: i64,
+    pub y: i64,
+}
+
+/**
+ * Falling rocks in a chamber of configurable width. Each settled row is a
+ * bitmask (bit `x` set when column `x` is filled), so checking a rock
+ * against the chamber is a handful of shifts and ands.
+ */
+#[derive(Debug, Clone)]
+pub struct TetrisSim {
+    pub width: i64,
+    pub rocks: Vec<Rock>,
+    pub jets: Vec<Flow>,
+    pub spawn: Spawn,
+    rows: Vec<u64>,
+    falling: Option<Falling>,
+    /// Index of the next rock to spawn
+    pub rock_index: usize,
+    /// Index of the next jet to push
+    pub jet_index: usize,
+    /// Number of rocks settled so far
+    pub settled: usize,
+}
+
+impl TetrisSim {
+    pub fn new(rocks: Vec<Rock>, jets: Vec<Flow>, width: i64) -> Self {
+        assert!(width > 0 && width <= 64, "Unsupported width: {}", width);
+        assert!(
+            !rocks.is_empty() && !jets.is_empty(),
+            "Missing rocks or jets"
+        );
+        if let Some(rock) = rocks.iter().find(|r| r.width > width) {
+            panic!("Rock wider than the chamber: {:?}", rock);
+        }
+
+        Self {
+            width,
+            rocks,
+            jets,
+            spawn: Spawn::default(),
+            rows: vec![],
+            falling: None,
+            rock_index: 0,
+            jet_index: 0,
+            settled: 0,
+        }
+    }
+
+    pub fn with_spawn(mut self, spawn: Spawn) -> Self {
+        self.spawn = spawn;
+        self
+    }
+
+    /// Height of the settled rocks
+    pub fn height(&self) -> i64 {
+        self.rows.len() as i64
+    }
+
+    pub fn falling(&self) -> Option<Falling> {
+        self.falling
+    }
+
+    /// Settled row `y`, empty above the top
+    pub fn row(&self, y: i64) -> u64 {
+        self.rows.get(y as usize).copied().unwrap_or(0)
+    }
+
+    fn overlaps(&self, rock: &Rock, x: i64, y: i64) -> bool {
+        if x < 0 || x + rock.width > self.width || y < 0 {
+            return true;
+        }
+        rock.rows
+            .iter()
+            .enumerate()
+            .any(|(dy, mask)| self.row(y + dy as i64) & (mask << x) != 0)
+    }
+
+    fn settle(&mut self, falling: Falling) {
+        let rock = &self.rocks[falling.rock];
+        for (dy, mask) in rock.rows.iter().enumerate() {
+            let y = (falling.y + dy as i64) as usize;
+            if y >= self.rows.len() {
+                self.rows.resize(y + 1, 0);
+            }
+            self.rows[y] |= mask << falling.x;
+        }
+        self.settled += 1;
+    }
+
+    /// Pushes the falling rock with the next jet, then moves it down by one.
+    /// A new rock is spawned first if none is falling. Returns the rock once
+    /// it can't move down anymore and has settled.
+    pub fn step(&mut self) -> Option<Falling> {
+        let mut falling = match self.falling {
+            Some(falling) => falling,
+            None => {
+                let falling = Falling {
+                    rock: self.rock_index,
+                    x: self.spawn.x,
+                    y: self.height() + self.spawn.y,
+                };
+                self.rock_index = (self.rock_index + 1) % self.rocks.len();
+                falling
+            }
+        };
+        let rock = &self.rocks[falling.rock];
+
+        let next_x = match self.jets[self.jet_index] {
+            Flow::Left => falling.x - 1,
+            Flow::Right => falling.x + 1,
+        };
+        self.jet_index = (self.jet_index + 1) % self.jets.len();
+        if !self.overlaps(rock, next_x, falling.y) {
+            falling.x = next_x;
+        }
+
+        if self.overlaps(rock, falling.x, falling.y - 1) {
+            self.falling = None;
+            self.settle(falling);
+            Some(falling)
+        } else {
+            falling.y -= 1;
+            self.falling = Some(falling);
+            None
+        }
+    }
+
+    /// Runs steps until the current (or next) rock settles
+    pub fn drop_rock(&mut self) -> Falling {
+        loop {
+            if let Some(settled) = self.step() {
+                return settled;
+            }
+        }
+    }
+
+    /// Depth of the highest rock in each column from the top, capped at
+    /// `SKYLINE_DEPTH` so that empty or buried columns don't grow with the
+    /// height. Along with the rock and jet indexes it tells when the
+    /// simulation starts repeating.
+    pub fn skyline(&self) -> Vec<i64> {
+        let top = self.height() - 1;
+        (0..self.width)
+            .map(|x| {
+                (0..SKYLINE_DEPTH.min(self.height()))
+                    .find(|depth| self.row(top - depth) & (1 << x) != 0)
+                    .unwrap_or(SKYLINE_DEPTH)
+            })
+            .collect()
+    }
+
+    /// The `rows` rows below `top` (excluded) with the top row first, as for
+    /// `print`. The bounds always cover the whole window, so that snapshots
+    /// can be animated.
+    pub fn to_map(&self, top: i64, rows: i64) -> Map<Cell> {
+        let mut map = Map::new();
+        for y in (top - rows).max(0)..top {
+            for x in 0..self.width {
+                if self.row(y) & (1 << x) != 0 {
+                    map.tiles
+                        .insert(Pos::new(x as i32, (top - 1 - y) as i32), Cell::Settled);
+                }
+            }
+        }
+        if let Some(falling) = self.falling {
+            let rock = &self.rocks[falling.rock];
+            for (dy, mask) in rock.rows.iter().enumerate() {
+                let y = falling.y + dy as i64;
+                for x in (0..rock.width).filter(|x| mask & (1 << x) != 0) {
+                    if y >= top - rows && y < top {
+                        let pos = Pos::new((falling.x + x) as i32, (top - 1 - y) as i32);
+                        map.tiles.insert(pos, Cell::Falling);
+                    }
+                }
+            }
+        }
+        map.bounds = Bounds::new(Pos::ZERO, Pos::new(self.width as i32 - 1, rows as i32 - 1));
+        map
+    }
+
+    /// Prints the `rows` topmost rows, with the falling rock in red
+    pub fn print(&self, rows: i64) {
+        let mut falling_rows = vec![];
+        let mut top = self.height();
+        if let Some(falling) = self.falling {
+            let rock = &self.rocks[falling.rock];
+            falling_rows = (0..falling.y + rock.height)
+                .map(|y| {
+                    let dy = y - falling.y;
+                    if dy >= 0 {
+                        rock.rows[dy as usize] << falling.x
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+            top = top.max(falling.y + rock.height);
+        }
+
+        println!("\nChamber (height = {}):", self.height());
+        for y in ((top - rows).max(0)..top).rev() {
+            let moving = falling_rows.get(y as usize).copied().unwrap_or(0);
+            print!("{}", "|".white());
+            for x in 0..self.width {
+                if moving & (1 << x) != 0 {
+                    print!("{}", "@".red());
+                } else if self.row(y) & (1 << x) != 0 {
+                    print!("{}", "#".white());
+                } else {
+                    print!("{}", ".".cyan());
+                }
+            }
+            println!("{}", "|".white());
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        part1::{get_rocks, parse_flow},
+        sim::*,
+    };
+
+    fn example() -> TetrisSim {
+        TetrisSim::new(get_rocks(), parse_flow(include_str!("../test.txt")), 7)
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut sim = example();
+        // The first rock falls 3 rows, then lands on the floor at the 4th step
+        assert_eq!(sim.step(), None);
+        assert_eq!(
+            sim.falling(),
+            Some(Falling {
+                rock: 0,
+                x: 3,
+                y: 2
+            })
+        );
+        assert_eq!(sim.step(), None);
+        assert_eq!(sim.step(), None);
+        assert_eq!(
+            sim.step(),
+            Some(Falling {
+                rock: 0,
+                x: 2,
+                y: 0
+            })
+        );
+        assert_eq!(sim.row(0), 0b0111100);
+        assert_eq!(sim.height(), 1);
+
+        let second = sim.drop_rock();
+        assert_eq!((second.rock, second.x, second.y), (1, 2, 1));
+        assert_eq!(sim.height(), 4);
+        assert_eq!(sim.settled, 2);
+    }
+
+    #[test]
+    fn test_custom_chamber() {
+        let rocks = Rock::parse_all("#\n\n##");
+        let jets = parse_flow(">");
+        let mut sim = TetrisSim::new(rocks, jets, 3).with_spawn(Spawn { x: 0, y: 2 });
+
+        // Every rock is pushed against the right wall
+        sim.drop_rock();
+        sim.drop_rock();
+        assert_eq!(sim.row(0), 0b100);
+        assert_eq!(sim.row(1), 0b110);
+        assert_eq!(sim.skyline(), vec![SKYLINE_DEPTH, 0, 0]);
+    }
+
+    #[test]
+    fn test_to_map() {
+        let mut sim = example();
+        sim.drop_rock();
+        sim.step();
+        // The plus shape pushed left and down by one, above the first rock
+        assert_eq!(
+            sim.to_map(7, 7).to_tiles('.'),
+            ".......\n..@....\n.@@@...\n..@....\n.......\n.......\n..####."
+        );
+        assert_eq!(sim.to_map(1, 4).bounds.height(), 4);
+    }
+}
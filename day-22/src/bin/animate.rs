@@ -0,0 +1,63 @@
+use day_22::{
+    part1::{run_maze_with, Board, Player},
+    part2::run_cube_maze_with,
+};
+use toolkit::animation::{Animation, Frame, Palette};
+
+/// Writes the walk on the board as an animation, usage:
+/// `cargo run -p day-22 --bin animate -- [input] [output.gif|output.png|dir] [--cube] [--every N]`
+///
+/// The cube is folded for the puzzle input only, not for the example.
+fn main() {
+    let mut paths = vec![];
+    let mut cube = false;
+    let mut every = 1;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cube" => cube = true,
+            "--every" => {
+                every = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|n| *n > 0)
+                    .expect("Invalid number of steps per frame")
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let mut paths = paths.into_iter();
+    let input = match paths.next() {
+        Some(path) => std::fs::read_to_string(path).expect("Cannot read input"),
+        None => include_str!("../../test.txt").to_string(),
+    };
+    let output = paths.next().unwrap_or_else(|| "board.gif".to_string());
+
+    let palette = Palette::by_char(
+        [20, 20, 30],
+        &[('.', [70, 40, 90]), ('#', [230, 230, 230])],
+        [240, 200, 60],
+    );
+    let mut animation = Animation::new(5);
+    let mut trail = vec![];
+    let moved = |board: &Board, player: &Player| {
+        trail.push(*player);
+        if (trail.len() - 1) % every == 0 {
+            let map = board.to_map(&trail);
+            animation.push(Frame::from_map(&map, &palette, map.bounds, 4));
+        }
+    };
+    let player = if cube {
+        run_cube_maze_with(&input, moved)
+    } else {
+        run_maze_with(&input, moved)
+    };
+    animation.save(&output).expect("Cannot write animation");
+    println!(
+        "Written {} frames to {}, password: {}",
+        animation.frames.len(),
+        output,
+        player.password()
+    );
+}
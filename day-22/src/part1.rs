@@ -4,6 +4,7 @@ use colored::Colorize;
 use enum_iterator::{next_cycle, previous_cycle, Sequence};
 use lazy_static::lazy_static;
 use regex::Regex;
+use toolkit::map::{self, Bounds, Map, Tile};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Pos {
@@ -112,6 +113,24 @@ impl Tile {
     }
 }
 
+/// A board position in a `Board::to_map` snapshot, with the direction the
+/// player last faced there, as drawn in the puzzle
+#[derive(Tile, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    #[tile('.', color = "red", background = "purple")]
+    Open,
+    #[tile('#', color = "white", background = "purple")]
+    Wall,
+    #[tile('^', color = "yellow", background = "purple")]
+    Up,
+    #[tile('>', color = "yellow", background = "purple")]
+    Right,
+    #[tile('v', color = "yellow", background = "purple")]
+    Down,
+    #[tile('<', color = "yellow", background = "purple")]
+    Left,
+}
+
 pub struct Board {
     pub tiles: HashMap<Pos, Tile>,
     pub min: Pos,
@@ -167,6 +186,35 @@ impl Board {
         }
     }
 
+    /// The open tiles and walls, with the positions of `trail` showing the
+    /// last direction faced there. The bounds stay the same for every trail.
+    pub fn to_map(&self, trail: &[Player]) -> Map<Cell> {
+        let mut map = Map::new();
+        for (pos, tile) in self.tiles.iter() {
+            let cell = match tile {
+                Tile::Open => Cell::Open,
+                Tile::Wall => Cell::Wall,
+                Tile::Empty | Tile::Portal(_) => continue,
+            };
+            map.tiles.insert(map::Pos::new(pos.x, pos.y), cell);
+        }
+        for player in trail {
+            let cell = match player.direction {
+                Direction::Up => Cell::Up,
+                Direction::Right => Cell::Right,
+                Direction::Down => Cell::Down,
+                Direction::Left => Cell::Left,
+            };
+            map.tiles
+                .insert(map::Pos::new(player.pos.x, player.pos.y), cell);
+        }
+        map.bounds = Bounds::new(
+            map::Pos::new(self.min.x, self.min.y),
+            map::Pos::new(self.max.x, self.max.y),
+        );
+        map
+    }
+
     #[allow(dead_code)]
     pub fn print(&self, player: Option<Player>) {
         for y in self.min.y - 1..=self.max.y + 1 {
@@ -264,8 +312,8 @@ fn create_flat_board(input: &str) -> Board {
     #[allow(clippy::needless_collect)]
     let edge_tiles: Vec<_> = board
         .tiles
-        .iter()
-        .flat_map(|(pos, _)| {
+        .keys()
+        .flat_map(|pos| {
             pos.neighbors()
                 .iter()
                 .filter_map(|neighbour| {
@@ -296,19 +344,32 @@ fn create_flat_board(input: &str) -> Board {
 }
 
 pub fn run_maze(input: &str) -> Player {
+    run_maze_with(input, |_, _| {})
+}
+
+/// Like `run_maze`, calling `moved` at the start and after every turn and
+/// single step
+pub fn run_maze_with(input: &str, mut moved: impl FnMut(&Board, &Player)) -> Player {
     let (board, moves) = input.split_once("\n\n").unwrap();
     let board = create_flat_board(board);
     let moves = Move::parse(moves);
     // board.print(None);
 
     let mut player = board.start_position();
+    moved(&board, &player);
 
     for m in moves {
         // println!("Move: {:?}", m);
         // board.print(Some(player));
         match m {
-            Move::TurnLeft => player.direction = previous_cycle(&player.direction).unwrap(),
-            Move::TurnRight => player.direction = next_cycle(&player.direction).unwrap(),
+            Move::TurnLeft => {
+                player.direction = previous_cycle(&player.direction).unwrap();
+                moved(&board, &player);
+            }
+            Move::TurnRight => {
+                player.direction = next_cycle(&player.direction).unwrap();
+                moved(&board, &player);
+            }
             Move::Forward(n) => {
                 for _ in 0..n {
                     let forward_pos = board
@@ -337,7 +398,8 @@ pub fn run_maze(input: &str) -> Player {
                             }
                         }
                         Tile::Empty => unreachable!(),
-                    }
+                    };
+                    moved(&board, &player);
                 }
             }
         }
@@ -355,4 +417,33 @@ pub mod tests {
         let input = include_str!("../test.txt");
         assert_eq!(run_maze(input).password(), 6032);
     }
+
+    #[test]
+    fn test_to_map() {
+        let input = include_str!("../test.txt");
+        let mut trail = vec![];
+        let player = run_maze_with(input, |_, player| trail.push(*player));
+        assert_eq!(trail.last(), Some(&player));
+
+        let board = create_flat_board(input.split_once("\n\n").unwrap().0);
+        let map = board.to_map(&trail);
+        // The path drawn in the puzzle description
+        let expected = [
+            "        >>v#",
+            "        .#v.",
+            "        #.v.",
+            "        ..v.",
+            "...#...v..v#",
+            ">>>v...>#.>>",
+            "..#v...#....",
+            "...>>>>v..#.",
+            "        ...#....",
+            "        .....#..",
+            "        .#......",
+            "        ......#.",
+        ];
+        let expected = expected.map(|row| format!("{:16}", row)).join("\n");
+        assert_eq!(map.to_tiles(' '), expected);
+        assert_eq!(map.bounds.width(), 16);
+    }
 }
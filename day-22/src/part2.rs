@@ -126,19 +126,32 @@ fn create_cube_portal(
  * work on the example input.
  */
 pub fn run_cube_maze(input: &str) -> Player {
+    run_cube_maze_with(input, |_, _| {})
+}
+
+/// Like `run_cube_maze`, calling `moved` at the start and after every turn
+/// and single step
+pub fn run_cube_maze_with(input: &str, mut moved: impl FnMut(&Board, &Player)) -> Player {
     let (board, moves) = input.split_once("\n\n").unwrap();
     let board = create_cube_board(board);
     let moves = Move::parse(moves);
     // board.print(None);
 
     let mut player = board.start_position();
+    moved(&board, &player);
 
     for m in moves {
         // println!("Move: {:?}", m);
         // board.print(Some(player));
         match m {
-            Move::TurnLeft => player.direction = previous_cycle(&player.direction).unwrap(),
-            Move::TurnRight => player.direction = next_cycle(&player.direction).unwrap(),
+            Move::TurnLeft => {
+                player.direction = previous_cycle(&player.direction).unwrap();
+                moved(&board, &player);
+            }
+            Move::TurnRight => {
+                player.direction = next_cycle(&player.direction).unwrap();
+                moved(&board, &player);
+            }
             Move::Forward(n) => {
                 for _ in 0..n {
                     let forward_pos = board
@@ -178,7 +191,8 @@ pub fn run_cube_maze(input: &str) -> Player {
                             }
                         }
                         Tile::Empty => unreachable!(),
-                    }
+                    };
+                    moved(&board, &player);
                 }
             }
         }
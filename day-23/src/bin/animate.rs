@@ -0,0 +1,46 @@
+use day_23::{
+    engine::{Grove, Rules},
+    part1::parse_map,
+};
+use toolkit::{
+    animation::{Animation, Frame, Palette},
+    map::{Bounds, Pos},
+};
+
+/// Writes the elves simulation as an animation, usage:
+/// `cargo run -p day-23 --bin animate -- [input] [output.gif|output.png|dir]`
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let input = match args.next() {
+        Some(path) => std::fs::read_to_string(path).expect("Cannot read input"),
+        None => include_str!("../../test.txt").to_string(),
+    };
+    let output = args.next().unwrap_or_else(|| "elves.gif".to_string());
+
+    let mut grove = Grove::new(&parse_map(&input), Rules::default());
+    let mut maps = vec![grove.to_map()];
+    while grove.step().moved > 0 {
+        maps.push(grove.to_map());
+    }
+
+    let bounds = maps.iter().fold(maps[0].bounds, |acc, map| {
+        Bounds::new(
+            Pos::new(
+                acc.min.x.min(map.bounds.min.x),
+                acc.min.y.min(map.bounds.min.y),
+            ),
+            Pos::new(
+                acc.max.x.max(map.bounds.max.x),
+                acc.max.y.max(map.bounds.max.y),
+            ),
+        )
+    });
+    let palette = Palette::by_char([20, 20, 30], &[('#', [60, 200, 90])], [255, 255, 255]);
+
+    let mut animation = Animation::new(10);
+    for map in maps.iter() {
+        animation.push(Frame::from_map(map, &palette, bounds, 4));
+    }
+    animation.save(&output).expect("Cannot write animation");
+    println!("Written {} frames to {}", animation.frames.len(), output);
+}
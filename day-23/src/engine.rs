@@ -0,0 +1,384 @@
+use itertools::Itertools;
+use toolkit::map::{Bounds, Pos};
+
+use crate::part1::{Map, Tile};
+
+/**
+ * A movement rule: an elf proposes to move by `step` when none of the
+ * `checks` positions (relative to the elf) is occupied.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub checks: Vec<Pos>,
+    pub step: Pos,
+}
+
+impl Rule {
+    pub fn new(checks: &[Pos], step: Pos) -> Self {
+        Self {
+            checks: checks.to_vec(),
+            step,
+        }
+    }
+}
+
+/// Order in which rules are considered at each round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    /// The first rule moves to the end of the list after every round
+    Cycle,
+    /// Rules are always considered in the same order
+    Fixed,
+}
+
+/// What happens when more than one elf proposes the same position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// No elf moves
+    Cancel,
+    /// The first elf (by reading order of the starting positions) moves,
+    /// the others stay
+    FirstWins,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub rules: Vec<Rule>,
+    pub rotation: Rotation,
+    pub conflict: Conflict,
+}
+
+impl Default for Rules {
+    /// The rules from the puzzle
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                Rule::new(&[Pos::N, Pos::NE, Pos::NW], Pos::N),
+                Rule::new(&[Pos::S, Pos::SE, Pos::SW], Pos::S),
+                Rule::new(&[Pos::W, Pos::NW, Pos::SW], Pos::W),
+                Rule::new(&[Pos::E, Pos::NE, Pos::SE], Pos::E),
+            ],
+            rotation: Rotation::Cycle,
+            conflict: Conflict::Cancel,
+        }
+    }
+}
+
+impl Rules {
+    /// Farthest distance an elf looks at or moves to
+    fn reach(&self) -> i32 {
+        self.rules
+            .iter()
+            .flat_map(|rule| rule.checks.iter().chain([&rule.step]))
+            .map(|p| p.x.abs().max(p.y.abs()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// An elf with none of these positions occupied stays idle
+    fn idle_checks(&self) -> Vec<Pos> {
+        self.rules
+            .iter()
+            .flat_map(|rule| rule.checks.iter().copied())
+            .unique()
+            .collect()
+    }
+
+    fn rule_at(&self, round: usize, i: usize) -> &Rule {
+        match self.rotation {
+            Rotation::Cycle => &self.rules[(i + round) % self.rules.len()],
+            Rotation::Fixed => &self.rules[i],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundStats {
+    /// Round number, starting from 1
+    pub round: usize,
+    pub moved: usize,
+    pub area: i32,
+    pub empty: i32,
+}
+
+/**
+ * Elves simulation backed by a dense grid. The grid grows when elves get
+ * close to its border, while bounds are tracked by counting elves in each
+ * row and column, so they never need a full scan.
+ */
+#[derive(Clone, Debug)]
+pub struct Grove {
+    pub rules: Rules,
+    pub elves: Vec<Pos>,
+    pub bounds: Bounds,
+    pub round: usize,
+    reach: i32,
+    idle_checks: Vec<Pos>,
+    cells: Vec<bool>,
+    claims: Vec<u8>,
+    area: Bounds,
+    rows: Vec<usize>,
+    columns: Vec<usize>,
+}
+
+impl Grove {
+    pub fn new(map: &Map, rules: Rules) -> Self {
+        let elves = map
+            .tiles
+            .iter()
+            .filter(|(_, tile)| **tile == Tile::Elf)
+            .map(|(pos, _)| *pos)
+            .sorted_by_key(|pos| (pos.y, pos.x))
+            .collect_vec();
+
+        let mut grove = Self {
+            reach: rules.reach(),
+            idle_checks: rules.idle_checks(),
+            rules,
+            bounds: Bounds::empty(),
+            round: 0,
+            cells: vec![],
+            claims: vec![],
+            area: Bounds::empty(),
+            rows: vec![],
+            columns: vec![],
+            elves,
+        };
+        grove.bounds = Bounds::new(
+            Pos::new(
+                grove.elves.iter().map(|p| p.x).min().unwrap_or(0),
+                grove.elves.iter().map(|p| p.y).min().unwrap_or(0),
+            ),
+            Pos::new(
+                grove.elves.iter().map(|p| p.x).max().unwrap_or(0),
+                grove.elves.iter().map(|p| p.y).max().unwrap_or(0),
+            ),
+        );
+        grove.resize();
+        grove
+    }
+
+    fn index(&self, pos: Pos) -> usize {
+        ((pos.y - self.area.min.y) * self.area.width() + (pos.x - self.area.min.x)) as usize
+    }
+
+    fn is_occupied(&self, pos: Pos) -> bool {
+        self.cells[self.index(pos)]
+    }
+
+    /// Reallocates the grid around the current bounds, leaving enough room
+    /// for elves to move for a while.
+    fn resize(&mut self) {
+        let margin = self
+            .reach
+            .max(self.bounds.width().max(self.bounds.height()) / 2);
+        let offset = Pos::new(margin, margin);
+        self.area = Bounds::new(
+            Pos::new(self.bounds.min.x - margin, self.bounds.min.y - margin),
+            self.bounds.max + offset,
+        );
+
+        let size = (self.area.width() * self.area.height()) as usize;
+        self.cells = vec![false; size];
+        self.claims = vec![0; size];
+        self.rows = vec![0; self.area.height() as usize];
+        self.columns = vec![0; self.area.width() as usize];
+        for i in 0..self.elves.len() {
+            let pos = self.elves[i];
+            let index = self.index(pos);
+            self.cells[index] = true;
+            self.rows[(pos.y - self.area.min.y) as usize] += 1;
+            self.columns[(pos.x - self.area.min.x) as usize] += 1;
+        }
+    }
+
+    fn needs_resize(&self) -> bool {
+        self.bounds.min.x - self.reach < self.area.min.x
+            || self.bounds.min.y - self.reach < self.area.min.y
+            || self.bounds.max.x + self.reach > self.area.max.x
+            || self.bounds.max.y + self.reach > self.area.max.y
+    }
+
+    fn propose(&self, pos: Pos) -> Option<Pos> {
+        if !self.idle_checks.iter().any(|&d| self.is_occupied(pos + d)) {
+            return None;
+        }
+
+        (0..self.rules.rules.len())
+            .map(|i| self.rules.rule_at(self.round, i))
+            .find(|rule| !rule.checks.iter().any(|&d| self.is_occupied(pos + d)))
+            .map(|rule| pos + rule.step)
+            .filter(|&target| !self.is_occupied(target))
+    }
+
+    /// Runs a single round, returning its stats
+    pub fn step(&mut self) -> RoundStats {
+        if self.needs_resize() {
+            self.resize();
+        }
+
+        let mut proposals = self
+            .elves
+            .iter()
+            .map(|&pos| self.propose(pos))
+            .collect_vec();
+
+        for proposal in proposals.iter_mut() {
+            let Some(target) = *proposal else { continue };
+            let index = self.index(target);
+            match self.rules.conflict {
+                Conflict::Cancel => self.claims[index] = self.claims[index].saturating_add(1),
+                Conflict::FirstWins if self.claims[index] > 0 => *proposal = None,
+                Conflict::FirstWins => self.claims[index] = 1,
+            }
+        }
+
+        let moves = proposals
+            .iter()
+            .enumerate()
+            .filter_map(|(i, proposal)| proposal.map(|target| (i, target)))
+            .filter(|&(_, target)| self.claims[self.index(target)] == 1)
+            .collect_vec();
+
+        for target in proposals.into_iter().flatten() {
+            let index = self.index(target);
+            self.claims[index] = 0;
+        }
+
+        // Targets are always empty, so leaving all the old positions first
+        // ensures no elf is overwritten
+        for &(i, _) in moves.iter() {
+            let pos = self.elves[i];
+            let index = self.index(pos);
+            self.cells[index] = false;
+            self.rows[(pos.y - self.area.min.y) as usize] -= 1;
+            self.columns[(pos.x - self.area.min.x) as usize] -= 1;
+        }
+        for &(i, target) in moves.iter() {
+            let index = self.index(target);
+            self.cells[index] = true;
+            self.rows[(target.y - self.area.min.y) as usize] += 1;
+            self.columns[(target.x - self.area.min.x) as usize] += 1;
+            self.elves[i] = target;
+            self.include(target);
+        }
+        self.shrink_bounds();
+
+        self.round += 1;
+        RoundStats {
+            round: self.round,
+            moved: moves.len(),
+            area: self.bounds.width() * self.bounds.height(),
+            empty: self.empty_tiles(),
+        }
+    }
+
+    fn include(&mut self, pos: Pos) {
+        self.bounds.min.x = self.bounds.min.x.min(pos.x);
+        self.bounds.min.y = self.bounds.min.y.min(pos.y);
+        self.bounds.max.x = self.bounds.max.x.max(pos.x);
+        self.bounds.max.y = self.bounds.max.y.max(pos.y);
+    }
+
+    fn shrink_bounds(&mut self) {
+        if self.elves.is_empty() {
+            return;
+        }
+        let (rows, columns, area) = (&self.rows, &self.columns, &self.area);
+        let row = |y: i32| rows[(y - area.min.y) as usize];
+        let column = |x: i32| columns[(x - area.min.x) as usize];
+
+        while row(self.bounds.min.y) == 0 {
+            self.bounds.min.y += 1;
+        }
+        while row(self.bounds.max.y) == 0 {
+            self.bounds.max.y -= 1;
+        }
+        while column(self.bounds.min.x) == 0 {
+            self.bounds.min.x += 1;
+        }
+        while column(self.bounds.max.x) == 0 {
+            self.bounds.max.x -= 1;
+        }
+    }
+
+    /// Runs rounds until no elf moves, returning the stats of that round
+    pub fn run_until_still(&mut self) -> RoundStats {
+        loop {
+            let stats = self.step();
+            if stats.moved == 0 {
+                return stats;
+            }
+        }
+    }
+
+    pub fn empty_tiles(&self) -> i32 {
+        self.bounds.width() * self.bounds.height() - self.elves.len() as i32
+    }
+
+    pub fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.tiles = self.elves.iter().map(|&pos| (pos, Tile::Elf)).collect();
+        map.bounds = self.bounds;
+        map
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{engine::*, part1::parse_map};
+
+    const SMALL: &str = ".....\n..##.\n..#..\n.....\n..##.\n.....";
+
+    #[test]
+    fn test_small_example() {
+        let mut grove = Grove::new(&parse_map(SMALL), Rules::default());
+        let stats = grove.step();
+        assert_eq!(
+            stats,
+            RoundStats {
+                round: 1,
+                moved: 3,
+                area: 10,
+                empty: 5
+            }
+        );
+
+        grove.step();
+        grove.step();
+        let expected = parse_map("..#..\n....#\n#....\n....#\n.....\n..#..");
+        assert_eq!(grove.to_map().tiles, expected.tiles);
+        assert_eq!(grove.run_until_still().round, 4);
+    }
+
+    #[test]
+    fn test_first_wins() {
+        let rules = Rules {
+            conflict: Conflict::FirstWins,
+            ..Rules::default()
+        };
+        let mut grove = Grove::new(&parse_map(SMALL), rules);
+        assert_eq!(grove.step().moved, 4);
+    }
+
+    #[test]
+    fn test_fixed_rotation() {
+        let rules = Rules {
+            rotation: Rotation::Fixed,
+            ..Rules::default()
+        };
+        let mut grove = Grove::new(&parse_map(SMALL), rules);
+        grove.step();
+        assert_eq!(grove.step().moved, 5);
+        assert_eq!(
+            grove.elves,
+            vec![
+                Pos::new(2, -1),
+                Pos::new(3, -1),
+                Pos::new(2, 1),
+                Pos::new(2, 5),
+                Pos::new(4, 3)
+            ]
+        );
+    }
+}
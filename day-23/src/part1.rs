@@ -1,100 +1,35 @@
-use std::{collections::HashMap, fmt::Display};
+use toolkit::map::{Map as BaseMap, Tile};
 
-use colored::Colorize;
-use toolkit::map::{Map as BaseMap, Pos, TileDisplay};
+use crate::engine::{Grove, Rules};
 
-type Map = BaseMap<Tile>;
+pub type Map = BaseMap<Tile>;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Tile, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Tile {
+    #[tile('.', color = "white", background = "black")]
     Empty,
+    #[tile('#', color = "green")]
     Elf,
 }
 
-impl TileDisplay for Tile {
-    fn map_print(&self) -> Box<dyn Display> {
-        match self {
-            Tile::Empty => Box::new(".".white().on_black()),
-            Tile::Elf => Box::new("#".green()),
-        }
-    }
-}
-
 pub fn parse_map(input: &str) -> Map {
-    Map::parse(input, |c, _, _| match c {
-        '.' => None,
-        '#' => Some(Tile::Elf),
-        _ => panic!("Unknown tile: {}", c),
-    })
-}
-
-fn are_occupied(map: &Map, pos: Pos, diffs: &[Pos]) -> bool {
-    diffs.iter().any(|&d| map.get(pos + d).is_some())
-}
-
-const DIRECTIONS: &[(&[Pos; 3], Pos); 4] = &[
-    (&[Pos::N, Pos::NE, Pos::NW], Pos::N),
-    (&[Pos::S, Pos::SE, Pos::SW], Pos::S),
-    (&[Pos::W, Pos::NW, Pos::SW], Pos::W),
-    (&[Pos::E, Pos::NE, Pos::SE], Pos::E),
-];
-
-fn elf_decision(map: &Map, pos: Pos, round: usize) -> Option<Pos> {
-    if map.all_neighbors(pos).is_empty() {
-        return None;
-    }
-
-    for i in 0..4 {
-        let (diffs, dir) = DIRECTIONS[(i + round) % 4];
-        if !are_occupied(map, pos, diffs) {
-            return Some(pos + dir);
-        }
-    }
-    None
-}
-
-pub fn simulate_step(map: &mut Map, round: usize) {
-    let mut targets = HashMap::new();
-    let decisions: Vec<_> = map
-        .tiles
-        .iter()
-        .map(|(&pos, _tile)| {
-            let decision = elf_decision(map, pos, round);
-            if let Some(decision_pos) = decision {
-                targets
-                    .entry(decision_pos)
-                    .and_modify(|x| *x += 1)
-                    .or_insert(1);
-            }
-            (pos, decision)
-        })
-        .collect();
-
-    let decisions: HashMap<_, _> = decisions
-        .iter()
-        .map(|(pos, decision)| match decision {
-            None => (*pos, Tile::Elf),
-            Some(decision) if targets[decision] >= 2 => (*pos, Tile::Elf),
-            Some(decision) => (*decision, Tile::Elf),
-        })
-        .collect();
-
-    map.tiles = decisions;
-    map.update_bounds();
+    Map::from_tiles(input, '.').unwrap_or_else(|e| panic!("{}", e))
 }
 
 pub fn simulate_elves(input: &str) -> i32 {
-    let mut map = parse_map(input);
+    let mut grove = Grove::new(&parse_map(input), Rules::default());
 
-    for i in 0..10 {
-        simulate_step(&mut map, i);
+    for _ in 0..10 {
+        grove.step();
     }
 
-    (map.bounds.width() * map.bounds.height()) - map.tiles.len() as i32
+    grove.empty_tiles()
 }
 
 #[cfg(test)]
 pub mod tests {
+    use toolkit::{golden::assert_golden, map::RenderOptions};
+
     use crate::part1::*;
 
     #[test]
@@ -102,4 +37,14 @@ pub mod tests {
         let input = include_str!("../test.txt");
         assert_eq!(simulate_elves(input), 110);
     }
+
+    #[test]
+    fn test_example_rounds() {
+        let mut grove = Grove::new(&parse_map(include_str!("../test.txt")), Rules::default());
+        for _ in 0..10 {
+            grove.step();
+        }
+        let rendered = grove.to_map().render(&RenderOptions::default());
+        assert_golden("golden/round-10.txt", &rendered);
+    }
 }
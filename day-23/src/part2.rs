@@ -1,22 +1,11 @@
-use crate::part1::{parse_map, simulate_step};
+use crate::{
+    engine::{Grove, Rules},
+    part1::parse_map,
+};
 
 pub fn find_stop_round(input: &str) -> usize {
-    let mut map = parse_map(input);
-    let mut round = 0;
-    loop {
-        let prev_map = map.clone();
-        simulate_step(&mut map, round);
-
-        if prev_map.tiles == map.tiles {
-            return round + 1;
-        }
-
-        if round > 1_000_000_000 {
-            panic!("Too many rounds");
-        }
-
-        round += 1;
-    }
+    let mut grove = Grove::new(&parse_map(input), Rules::default());
+    grove.run_until_still().round
 }
 
 #[cfg(test)]
@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+use day_24::part1::{parse_blizzard_map, reachable, simulate_blizzard, valley_map};
+use toolkit::{
+    animation::{Animation, Frame, Palette},
+    map::Pos,
+};
+
+/// Writes the blizzards and every position the expedition can reach, minute
+/// by minute until it gets to the exit, as an animation, usage:
+/// `cargo run -p day-24 --bin animate -- [input] [output.gif|output.png|dir]`
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let input = match args.next() {
+        Some(path) => std::fs::read_to_string(path).expect("Cannot read input"),
+        None => include_str!("../../test.txt").to_string(),
+    };
+    let output = args.next().unwrap_or_else(|| "valley.gif".to_string());
+
+    let (map, mut blizzards) = parse_blizzard_map(&input);
+    let exit = Pos::new(map.bounds.width() - 2, map.bounds.height() - 1);
+    let palette = Palette::by_char(
+        [20, 20, 30],
+        &[('#', [120, 120, 130]), ('E', [60, 200, 90])],
+        [150, 200, 240],
+    );
+
+    let mut animation = Animation::new(20);
+    let mut expedition = HashSet::from([Pos::new(1, 0)]);
+    loop {
+        let valley = valley_map(&map, &blizzards, &expedition);
+        animation.push(Frame::from_map(&valley, &palette, valley.bounds, 6));
+        if expedition.contains(&exit) || expedition.is_empty() {
+            break;
+        }
+        simulate_blizzard(&map, &mut blizzards);
+        expedition = reachable(&map, &blizzards, &expedition);
+    }
+    animation.save(&output).expect("Cannot write animation");
+    println!("Written {} frames to {}", animation.frames.len(), output);
+}
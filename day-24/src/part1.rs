@@ -1,5 +1,5 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use toolkit::map::{Map, Pos};
+use toolkit::map::{Map, Pos, Tile};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Tile {
@@ -9,6 +9,26 @@ pub enum Tile {
     Exit,
 }
 
+/// A valley position in a `valley_map` snapshot
+#[derive(Tile, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    #[tile('#', color = "white")]
+    Wall,
+    #[tile('^', color = "cyan")]
+    Up,
+    #[tile('>', color = "cyan")]
+    Right,
+    #[tile('v', color = "cyan")]
+    Down,
+    #[tile('<', color = "cyan")]
+    Left,
+    /// Several blizzards in the same position
+    #[tile('*', color = "bright cyan")]
+    Blizzards,
+    #[tile('E', color = "green")]
+    Expedition,
+}
+
 pub fn parse_blizzard(c: char) -> Pos {
     match c {
         '>' => Pos::E,
@@ -100,6 +120,58 @@ pub fn parse_blizzard_map(input: &str) -> (Map<Tile>, BlizzardsMap) {
     (map, blizzards)
 }
 
+/// Every position the expedition can be in after the blizzards moved, coming
+/// from any of `positions`
+pub fn reachable(
+    map: &Map<Tile>,
+    blizzards: &BlizzardsMap,
+    positions: &HashSet<Pos>,
+) -> HashSet<Pos> {
+    positions
+        .iter()
+        .flat_map(|pos| {
+            let player = Player {
+                pos: *pos,
+                minutes: 0,
+                target: (false, Tile::Exit),
+            };
+            available_moves(map, blizzards, player)
+        })
+        .collect()
+}
+
+/// The walls and blizzards, with every position in `expedition` where no
+/// blizzard is. The bounds are the ones of the valley.
+pub fn valley_map(
+    map: &Map<Tile>,
+    blizzards: &BlizzardsMap,
+    expedition: &HashSet<Pos>,
+) -> Map<Cell> {
+    let mut valley = Map::new();
+    for (pos, tile) in map.tiles.iter() {
+        if *tile == Tile::Wall {
+            valley.tiles.insert(*pos, Cell::Wall);
+        }
+    }
+    for (pos, current) in blizzards.iter() {
+        let cell = match current.as_slice() {
+            [single] => match single.direction {
+                Pos::N => Cell::Up,
+                Pos::E => Cell::Right,
+                Pos::S => Cell::Down,
+                _ => Cell::Left,
+            },
+            _ => Cell::Blizzards,
+        };
+        valley.tiles.insert(*pos, cell);
+    }
+    for pos in expedition.iter() {
+        valley.tiles.entry(*pos).or_insert(Cell::Expedition);
+    }
+    valley.bounds = map.bounds;
+    valley
+}
+
 pub fn navigate_blizzards(input: &str) -> Option<usize> {
     let (map, mut blizzards) = parse_blizzard_map(input);
 
@@ -156,4 +228,31 @@ pub mod tests {
         let input = include_str!("../test.txt");
         assert_eq!(navigate_blizzards(input), Some(18));
     }
+
+    #[test]
+    fn test_valley_map() {
+        let input = include_str!("../test.txt");
+        let (map, mut blizzards) = parse_blizzard_map(input);
+        let start = HashSet::from([Pos::new(1, 0)]);
+        assert_eq!(
+            valley_map(&map, &blizzards, &start).to_tiles('.'),
+            "#E######\n#>>.<^<#\n#.<..<<#\n#>v.><>#\n#<^v^^>#\n######.#"
+        );
+
+        // The expedition spreads until it reaches the exit after 18 minutes
+        let exit = Pos::new(map.bounds.width() - 2, map.bounds.height() - 1);
+        let mut expedition = start;
+        let mut minutes = 0;
+        while !expedition.contains(&exit) {
+            simulate_blizzard(&map, &mut blizzards);
+            expedition = reachable(&map, &blizzards, &expedition);
+            minutes += 1;
+        }
+        assert_eq!(minutes, 18);
+        // As drawn in the puzzle, with `*` for several blizzards
+        assert_eq!(
+            valley_map(&map, &blizzards, &HashSet::from([exit])).to_tiles('.'),
+            "#.######\n#>*.<.<#\n#.*v^*<#\n#>..>*>#\n#<....>#\n######E#"
+        );
+    }
 }
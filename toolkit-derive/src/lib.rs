@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitChar, LitStr, Variant};
+
+/// How a variant is shown on a map, from `#[tile('#', color = "green")]`
+struct TileAttr {
+    ident: Ident,
+    char: LitChar,
+    color: Option<LitStr>,
+    background: Option<LitStr>,
+}
+
+impl TileAttr {
+    fn parse(variant: &Variant) -> Result<Self, Error> {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                &variant.fields,
+                "Tile can only be derived for variants without fields",
+            ));
+        }
+        let attr = variant
+            .attrs
+            .iter()
+            .find(|a| a.path().is_ident("tile"))
+            .ok_or_else(|| {
+                Error::new_spanned(
+                    &variant.ident,
+                    format!("missing #[tile('c')] attribute on `{}`", variant.ident),
+                )
+            })?;
+
+        let mut tile = TileAttr {
+            ident: variant.ident.clone(),
+            char: LitChar::new(' ', Span::call_site()),
+            color: None,
+            background: None,
+        };
+        attr.parse_args_with(|input: syn::parse::ParseStream| {
+            tile.char = input.parse()?;
+            while !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+                if input.is_empty() {
+                    break;
+                }
+                let key: Ident = input.parse()?;
+                input.parse::<syn::Token![=]>()?;
+                let value: LitStr = input.parse()?;
+                match key.to_string().as_str() {
+                    "color" => tile.color = Some(value),
+                    "background" => tile.background = Some(value),
+                    _ => return Err(Error::new_spanned(key, "expected `color` or `background`")),
+                }
+            }
+            Ok(())
+        })?;
+        Ok(tile)
+    }
+}
+
+fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "Tile can only be derived for enums",
+        ));
+    };
+
+    // Reports every problem at once rather than one per build
+    let mut errors: Option<Error> = None;
+    let mut report = |error: Error| match errors.as_mut() {
+        Some(errors) => errors.combine(error),
+        None => errors = Some(error),
+    };
+
+    let mut tiles = vec![];
+    for variant in data.variants.iter() {
+        match TileAttr::parse(variant) {
+            Ok(tile) => tiles.push(tile),
+            Err(error) => report(error),
+        }
+    }
+
+    let mut seen: HashMap<char, &Ident> = HashMap::new();
+    for tile in tiles.iter() {
+        if let Some(previous) = seen.insert(tile.char.value(), &tile.ident) {
+            report(Error::new_spanned(
+                &tile.char,
+                format!(
+                    "duplicate tile character {:?}, already used by `{}`",
+                    tile.char.value(),
+                    previous
+                ),
+            ));
+        }
+    }
+    // `colored` paints unknown color names white, without any warning
+    for color in tiles
+        .iter()
+        .flat_map(|t| t.color.iter().chain(t.background.iter()))
+    {
+        if color.value().parse::<colored::Color>().is_err() {
+            report(Error::new_spanned(
+                color,
+                format!("unknown color {:?}", color.value()),
+            ));
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let idents = tiles.iter().map(|t| &t.ident).collect::<Vec<_>>();
+    let chars = tiles.iter().map(|t| &t.char).collect::<Vec<_>>();
+    let option = |value: &Option<LitStr>| match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None),
+    };
+    let colors = tiles.iter().map(|t| option(&t.color));
+    let backgrounds = tiles.iter().map(|t| option(&t.background));
+
+    Ok(quote! {
+        impl #impl_generics ::toolkit::map::TileChar for #name #ty_generics #where_clause {
+            fn from_char(c: char) -> Option<Self> {
+                match c {
+                    #(#chars => Some(Self::#idents),)*
+                    _ => None,
+                }
+            }
+
+            fn to_char(&self) -> char {
+                match self {
+                    #(Self::#idents => #chars,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::toolkit::map::TileDisplay for #name #ty_generics #where_clause {
+            fn map_print(&self) -> Box<dyn ::std::fmt::Display> {
+                let (color, background): (Option<&str>, Option<&str>) = match self {
+                    #(Self::#idents => (#colors, #backgrounds),)*
+                };
+                ::toolkit::map::colored_tile(::toolkit::map::TileChar::to_char(self), color, background)
+            }
+        }
+    })
+}
+
+/// Derives `TileChar` and `TileDisplay` for an enum of unit variants, each
+/// with a `#[tile('c')]` attribute giving its character on a map, and
+/// optionally `color` and `background` names as understood by `colored`.
+#[proc_macro_derive(Tile, attributes(tile))]
+pub fn derive_tile(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,345 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use itertools::Itertools;
+
+use crate::map::{render::strip_ansi, Bounds, Map, Pos, TileDisplay};
+
+pub type Rgb = [u8; 3];
+
+/**
+ * Maps tiles to colors, with a background color for the positions without
+ * a tile.
+ */
+pub struct Palette<T> {
+    pub empty: Rgb,
+    color: Box<dyn Fn(&T) -> Rgb>,
+}
+
+impl<T> Palette<T> {
+    pub fn new(empty: Rgb, color: impl Fn(&T) -> Rgb + 'static) -> Self {
+        Self {
+            empty,
+            color: Box::new(color),
+        }
+    }
+
+    pub fn color(&self, tile: Option<&T>) -> Rgb {
+        tile.map(|t| (self.color)(t)).unwrap_or(self.empty)
+    }
+}
+
+impl<T: TileDisplay> Palette<T> {
+    /// Picks colors by the character printed by `TileDisplay`, ignoring the
+    /// terminal colors. Unknown characters are drawn with `fallback`.
+    pub fn by_char(empty: Rgb, colors: &[(char, Rgb)], fallback: Rgb) -> Self {
+        let colors = colors.to_vec();
+        Self::new(empty, move |tile: &T| {
+            let printed = strip_ansi(&tile.map_print().to_string());
+            printed
+                .chars()
+                .next()
+                .and_then(|c| colors.iter().find(|(k, _)| *k == c))
+                .map(|(_, rgb)| *rgb)
+                .unwrap_or(fallback)
+        })
+    }
+}
+
+/// A single RGB image, each tile drawn as a `scale`×`scale` square.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+
+impl Frame {
+    /// Renders the map inside `bounds`. Animations should use the same
+    /// bounds for every frame, since the map bounds change over time.
+    pub fn from_map<T>(map: &Map<T>, palette: &Palette<T>, bounds: Bounds, scale: usize) -> Self {
+        let width = bounds.width() as usize * scale;
+        let height = bounds.height() as usize * scale;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in bounds.min.y..=bounds.max.y {
+            let row = (bounds.min.x..=bounds.max.x)
+                .map(|x| palette.color(map.get(Pos::new(x, y))))
+                .flat_map(|rgb| std::iter::repeat_n(rgb, scale))
+                .collect_vec();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&row);
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Binary PPM (P6) encoding
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut data = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.extend(self.pixels.iter().flatten());
+        data
+    }
+}
+
+/**
+ * A sequence of frames, all with the same size, that can be written as an
+ * animated GIF, an animated PNG or a set of PPM files.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    /// Delay between frames, in hundredths of second
+    pub delay: u16,
+}
+
+impl Animation {
+    pub fn new(delay: u16) -> Self {
+        Self {
+            frames: vec![],
+            delay,
+        }
+    }
+
+    pub fn push(&mut self, frame: Frame) {
+        if let Some(first) = self.frames.first() {
+            assert!(
+                first.width == frame.width && first.height == frame.height,
+                "Frame size changed from {}x{} to {}x{}",
+                first.width,
+                first.height,
+                frame.width,
+                frame.height
+            );
+        }
+        self.frames.push(frame);
+    }
+
+    /// Writes `prefix_0000.ppm`, `prefix_0001.ppm`... in `dir`
+    pub fn write_ppm_frames(&self, dir: impl AsRef<Path>, prefix: &str) -> io::Result<()> {
+        fs::create_dir_all(&dir)?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            let path = dir.as_ref().join(format!("{}_{:04}.ppm", prefix, i));
+            File::create(path)?.write_all(&frame.to_ppm())?;
+        }
+        Ok(())
+    }
+
+    /// Writes an animated GIF. Frames can't use more than 256 colors
+    /// overall, nor be larger than 65535 pixels on a side.
+    pub fn write_gif(&self, writer: impl Write) -> io::Result<()> {
+        let Some(first) = self.frames.first() else {
+            return Ok(());
+        };
+        let (width, height): (u16, u16) = (
+            dimension(first.width, "GIF")?,
+            dimension(first.height, "GIF")?,
+        );
+
+        let mut colors: Vec<Rgb> = vec![];
+        let mut indexes: HashMap<Rgb, u8> = HashMap::new();
+        for rgb in self.frames.iter().flat_map(|f| f.pixels.iter()) {
+            if !indexes.contains_key(rgb) {
+                let index = u8::try_from(colors.len()).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Too many colors for a GIF, at most 256 are allowed",
+                    )
+                })?;
+                indexes.insert(*rgb, index);
+                colors.push(*rgb);
+            }
+        }
+
+        let palette = colors.iter().flatten().copied().collect_vec();
+        let mut encoder =
+            gif::Encoder::new(writer, width, height, &palette).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+
+        for frame in self.frames.iter() {
+            let buffer = frame.pixels.iter().map(|rgb| indexes[rgb]).collect_vec();
+            let mut gif_frame = gif::Frame {
+                width,
+                height,
+                buffer: buffer.into(),
+                delay: self.delay,
+                ..gif::Frame::default()
+            };
+            gif_frame.dispose = gif::DisposalMethod::Keep;
+            encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    /// Writes an animated PNG
+    pub fn write_apng(&self, writer: impl Write) -> io::Result<()> {
+        let Some(first) = self.frames.first() else {
+            return Ok(());
+        };
+        let mut encoder = png::Encoder::new(
+            writer,
+            dimension(first.width, "PNG")?,
+            dimension(first.height, "PNG")?,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .set_animated(self.frames.len() as u32, 0)
+            .map_err(io::Error::other)?;
+        encoder
+            .set_frame_delay(self.delay, 100)
+            .map_err(io::Error::other)?;
+
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        for frame in self.frames.iter() {
+            let data = frame.pixels.iter().flatten().copied().collect_vec();
+            writer.write_image_data(&data).map_err(io::Error::other)?;
+        }
+        writer.finish().map_err(io::Error::other)
+    }
+
+    /// Writes the animation choosing the format from the extension: `gif`,
+    /// `png` (animated), or a directory of `ppm` frames without extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        match path.extension().map(|e| e.to_str()) {
+            Some(Some("gif")) => self.write_gif(BufWriter::new(File::create(path)?)),
+            Some(Some("png")) => self.write_apng(BufWriter::new(File::create(path)?)),
+            None => self.write_ppm_frames(path, "frame"),
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown animation format for {}, use .gif, .png or a directory",
+                    path.display()
+                ),
+            )),
+        }
+    }
+}
+
+/// A frame size converted for an encoder, or an error if it doesn't fit
+fn dimension<T: TryFrom<usize>>(size: usize, format: &str) -> io::Result<T> {
+    T::try_from(size).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Frames are too large for a {}: {} pixels", format, size),
+        )
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::fmt::Display;
+
+    use super::*;
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Tile {
+        Wall,
+        Sand,
+    }
+
+    impl TileDisplay for Tile {
+        fn map_print(&self) -> Box<dyn Display> {
+            match self {
+                Tile::Wall => Box::new("\x1b[32m#\x1b[0m"),
+                Tile::Sand => Box::new("o"),
+            }
+        }
+    }
+
+    const BLACK: Rgb = [0, 0, 0];
+    const GREEN: Rgb = [0, 255, 0];
+    const YELLOW: Rgb = [255, 255, 0];
+
+    fn animation() -> Animation {
+        let palette = Palette::by_char(BLACK, &[('#', GREEN), ('o', YELLOW)], BLACK);
+        let map = Map::parse("#o\n.#", |c, _, _| match c {
+            '#' => Some(Tile::Wall),
+            'o' => Some(Tile::Sand),
+            _ => None,
+        });
+        let bounds = map.bounds;
+
+        let mut animation = Animation::new(10);
+        animation.push(Frame::from_map(&map, &palette, bounds, 1));
+        animation.push(Frame::from_map(&Map::new(), &palette, bounds, 1));
+        animation
+    }
+
+    #[test]
+    fn test_frame() {
+        let animation = animation();
+        assert_eq!(
+            animation.frames[0].pixels,
+            vec![GREEN, YELLOW, BLACK, GREEN]
+        );
+        assert_eq!(animation.frames[1].pixels, vec![BLACK; 4]);
+
+        let ppm = animation.frames[0].to_ppm();
+        assert!(ppm.starts_with(b"P6\n2 2\n255\n"));
+        assert_eq!(ppm.len(), 11 + 2 * 2 * 3);
+    }
+
+    #[test]
+    fn test_scale() {
+        let map = Map::parse("#", |_, _, _| Some(Tile::Wall));
+        let palette = Palette::new(BLACK, |_: &Tile| GREEN);
+        let frame = Frame::from_map(&map, &palette, map.bounds, 3);
+        assert_eq!((frame.width, frame.height), (3, 3));
+        assert_eq!(frame.pixels, vec![GREEN; 9]);
+    }
+
+    #[test]
+    fn test_encoders() {
+        let animation = animation();
+
+        let mut gif = vec![];
+        animation.write_gif(&mut gif).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+
+        let mut apng = vec![];
+        animation.write_apng(&mut apng).unwrap();
+        assert!(apng.starts_with(b"\x89PNG"));
+        assert!(apng.windows(4).any(|w| w == b"acTL"));
+    }
+
+    #[test]
+    fn test_encoder_limits() {
+        let mut wide = Animation::new(10);
+        wide.push(Frame {
+            width: 70_000,
+            height: 1,
+            pixels: vec![BLACK; 70_000],
+        });
+        let error = wide.write_gif(vec![]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let mut colorful = Animation::new(10);
+        colorful.push(Frame {
+            width: 300,
+            height: 1,
+            pixels: (0..300)
+                .map(|i| [(i % 256) as u8, (i / 256) as u8, 0])
+                .collect(),
+        });
+        let error = colorful.write_gif(vec![]).unwrap_err();
+        assert!(error.to_string().contains("at most 256"));
+        colorful.frames[0].pixels.truncate(256);
+        colorful.frames[0].width = 256;
+        assert!(colorful.write_gif(vec![]).is_ok());
+
+        let error = animation().save("elves.mp4").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
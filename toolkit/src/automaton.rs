@@ -0,0 +1,220 @@
+use std::{collections::HashSet, mem};
+
+use itertools::Itertools;
+
+use crate::map::{Map, Neighbor, Pos};
+
+/// The cells a tile looks at when it's updated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// N, W, E, S
+    VonNeumann,
+    /// All the eight surrounding cells
+    Moore,
+    /// Offsets relative to the updated cell
+    Custom(Vec<Pos>),
+}
+
+impl Neighborhood {
+    pub fn offsets(&self) -> Vec<Pos> {
+        match self {
+            Neighborhood::VonNeumann => Pos::ZERO.neighbors(),
+            Neighborhood::Moore => Pos::ZERO.all_neighbors(),
+            Neighborhood::Custom(offsets) => offsets.clone(),
+        }
+    }
+}
+
+/// How the rule is applied to the cells of a step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Update {
+    /// Every cell is computed from the previous step
+    Synchronous,
+    /// Cells are updated in place in reading order, so they see the cells
+    /// already updated in the same step
+    Sequential,
+}
+
+/**
+ * A cellular automaton over a `Map`. At each step the rule receives the
+ * position, its current tile (if any) and the occupied neighbors, and
+ * returns the new tile. Only occupied cells and their neighbors are
+ * evaluated, so empty cells can be filled only next to existing tiles.
+ */
+#[derive(Clone, Debug)]
+pub struct Automaton<T> {
+    pub map: Map<T>,
+    pub update: Update,
+    pub steps: usize,
+    offsets: Vec<Pos>,
+    back: Map<T>,
+}
+
+impl<T: Clone + PartialEq> Automaton<T> {
+    pub fn new(map: Map<T>, neighborhood: Neighborhood, update: Update) -> Self {
+        Self {
+            map,
+            update,
+            steps: 0,
+            offsets: neighborhood.offsets(),
+            back: Map::new(),
+        }
+    }
+
+    fn neighbors<'a>(map: &'a Map<T>, offsets: &[Pos], pos: Pos) -> Vec<Neighbor<'a, T>> {
+        offsets
+            .iter()
+            .filter_map(|&d| map.get(pos + d).map(|t| (pos + d, t)))
+            .collect()
+    }
+
+    /// Cells to evaluate, in reading order
+    fn candidates(&self) -> Vec<Pos> {
+        let mut candidates: HashSet<Pos> = self.map.tiles.keys().copied().collect();
+        for pos in self.map.tiles.keys() {
+            // A cell sees `pos` when `pos` is in its neighborhood
+            candidates.extend(self.offsets.iter().map(|&d| *pos + Pos::new(-d.x, -d.y)));
+        }
+        candidates
+            .into_iter()
+            .sorted_by_key(|p| (p.y, p.x))
+            .collect()
+    }
+
+    /// Runs a single step, returning whether any cell changed
+    pub fn step(
+        &mut self,
+        mut rule: impl FnMut(Pos, Option<&T>, &[Neighbor<T>]) -> Option<T>,
+    ) -> bool {
+        let candidates = self.candidates();
+        let mut changed = false;
+
+        match self.update {
+            Update::Synchronous => {
+                self.back.tiles.clear();
+                for pos in candidates {
+                    let current = self.map.get(pos);
+                    let neighbors = Self::neighbors(&self.map, &self.offsets, pos);
+                    let next = rule(pos, current, &neighbors);
+                    changed |= next.as_ref() != current;
+                    if let Some(tile) = next {
+                        self.back.tiles.insert(pos, tile);
+                    }
+                }
+                mem::swap(&mut self.map, &mut self.back);
+            }
+            Update::Sequential => {
+                for pos in candidates {
+                    let current = self.map.get(pos);
+                    let neighbors = Self::neighbors(&self.map, &self.offsets, pos);
+                    let next = rule(pos, current, &neighbors);
+                    if next.as_ref() == current {
+                        continue;
+                    }
+                    changed = true;
+                    match next {
+                        Some(tile) => self.map.tiles.insert(pos, tile),
+                        None => self.map.tiles.remove(&pos),
+                    };
+                }
+            }
+        }
+
+        self.map.update_bounds();
+        self.steps += 1;
+        changed
+    }
+
+    /// Runs `steps` steps, calling `on_step` with the step number (starting
+    /// from 1) and the map after each of them.
+    pub fn run(
+        &mut self,
+        steps: usize,
+        mut rule: impl FnMut(Pos, Option<&T>, &[Neighbor<T>]) -> Option<T>,
+        mut on_step: impl FnMut(usize, &Map<T>),
+    ) {
+        for _ in 0..steps {
+            self.step(&mut rule);
+            on_step(self.steps, &self.map);
+        }
+    }
+
+    /// Runs until a step doesn't change any cell, returning the number of
+    /// steps run, including the last one.
+    pub fn run_until_fixpoint(
+        &mut self,
+        mut rule: impl FnMut(Pos, Option<&T>, &[Neighbor<T>]) -> Option<T>,
+        mut on_step: impl FnMut(usize, &Map<T>),
+    ) -> usize {
+        let start = self.steps;
+        loop {
+            let changed = self.step(&mut rule);
+            on_step(self.steps, &self.map);
+            if !changed {
+                return self.steps - start;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn life(_: Pos, tile: Option<&bool>, neighbors: &[Neighbor<bool>]) -> Option<bool> {
+        match (tile, neighbors.len()) {
+            (Some(_), 2 | 3) | (None, 3) => Some(true),
+            _ => None,
+        }
+    }
+
+    fn parse(input: &str) -> Map<bool> {
+        Map::parse(input, |c, _, _| (c == '#').then_some(true))
+    }
+
+    #[test]
+    fn test_blinker() {
+        let map = parse(".....\n..#..\n..#..\n..#..\n.....");
+        let mut automaton = Automaton::new(map.clone(), Neighborhood::Moore, Update::Synchronous);
+
+        let mut rendered = vec![];
+        automaton.run(2, life, |step, map| rendered.push((step, map.tiles.len())));
+        assert_eq!(rendered, vec![(1, 3), (2, 3)]);
+        assert_eq!(automaton.map.tiles, map.tiles);
+
+        automaton.step(life);
+        assert_eq!(automaton.map.bounds.width(), 3);
+        assert_eq!(automaton.map.bounds.height(), 1);
+    }
+
+    #[test]
+    fn test_fixpoint() {
+        // A block is a still life
+        let map = parse("##\n##");
+        let mut automaton = Automaton::new(map, Neighborhood::Moore, Update::Synchronous);
+        assert_eq!(automaton.run_until_fixpoint(life, |_, _| {}), 1);
+    }
+
+    #[test]
+    fn test_sequential() {
+        // A tile survives only without a western neighbor: sequentially, a
+        // removed tile lets the next one survive
+        let map = parse("####");
+        let rule = |pos: Pos, tile: Option<&bool>, neighbors: &[Neighbor<bool>]| {
+            tile.copied()
+                .filter(|_| !neighbors.iter().any(|(p, _)| *p == pos + Pos::W))
+        };
+
+        let mut sequential =
+            Automaton::new(map.clone(), Neighborhood::VonNeumann, Update::Sequential);
+        assert!(sequential.step(rule));
+        assert_eq!(
+            sequential.map.tiles.keys().sorted().collect_vec(),
+            vec![&Pos::new(0, 0), &Pos::new(2, 0)]
+        );
+
+        let mut synchronous = Automaton::new(map, Neighborhood::VonNeumann, Update::Synchronous);
+        assert_eq!(synchronous.run_until_fixpoint(rule, |_, _| {}), 2);
+        assert_eq!(synchronous.map.tiles.len(), 1);
+    }
+}
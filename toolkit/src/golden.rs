@@ -0,0 +1,78 @@
+use std::{env, fs, path::PathBuf};
+
+/// Set to any value to write the golden files instead of comparing them
+pub const UPDATE_VAR: &str = "UPDATE_GOLDEN";
+
+/// Golden paths are relative to the crate being tested
+fn golden_path(path: &str) -> PathBuf {
+    let root = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(root).join(path)
+}
+
+/// Compares `actual` with the content of the golden file at `path`,
+/// relative to the crate root. With `UPDATE_GOLDEN=1` in the environment,
+/// the file is written with `actual` instead.
+#[track_caller]
+pub fn assert_golden(path: &str, actual: &str) {
+    check_golden(path, actual, env::var_os(UPDATE_VAR).is_some());
+}
+
+#[track_caller]
+fn check_golden(path: &str, actual: &str, update: bool) {
+    let file = golden_path(path);
+    if update {
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(&file, actual)
+            .unwrap_or_else(|e| panic!("Can't write {}: {}", file.display(), e));
+        return;
+    }
+
+    let expected = fs::read_to_string(&file).unwrap_or_else(|e| {
+        panic!(
+            "Can't read {}: {}, run with {}=1 to create it",
+            file.display(),
+            e,
+            UPDATE_VAR
+        )
+    });
+    if expected != actual {
+        let line = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(e, a)| e != a)
+            .unwrap_or_else(|| expected.lines().count().min(actual.lines().count()));
+        panic!(
+            "{} differs from line {}, run with {}=1 to update it\n\
+             --- expected\n{}\n--- actual\n{}",
+            file.display(),
+            line + 1,
+            UPDATE_VAR,
+            expected,
+            actual
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match() {
+        check_golden("golden/lines.txt", "one\ntwo\nthree\n", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "differs from line 2")]
+    fn test_mismatch() {
+        check_golden("golden/lines.txt", "one\n2\nthree\n", false);
+    }
+
+    #[test]
+    #[should_panic(expected = "run with UPDATE_GOLDEN=1 to create it")]
+    fn test_missing() {
+        check_golden("golden/missing.txt", "", false);
+    }
+}
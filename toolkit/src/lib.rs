@@ -1 +1,7 @@
+// Lets `#[derive(Tile)]` refer to `::toolkit` from inside this crate
+extern crate self as toolkit;
+
+pub mod animation;
+pub mod automaton;
+pub mod golden;
 pub mod map;
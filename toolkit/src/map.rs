@@ -1,8 +1,15 @@
 pub mod bounds;
+pub mod direction;
 pub mod pos;
+pub mod render;
+pub mod tile;
 
 pub use bounds::*;
+pub use direction::{Command, Direction4, Direction8, ParseDirectionError, Turn, Walker};
 pub use pos::*;
+pub use render::RenderOptions;
+pub use tile::{colored_tile, ParseTileError, TileChar};
+pub use toolkit_derive::Tile;
 
 use std::{collections::HashMap, fmt::Display, ops::Index};
 
@@ -57,7 +64,7 @@ impl<T> Map<T> {
         self.tiles.get(&pos)
     }
 
-    pub fn all_neighbors(&self, pos: Pos) -> Vec<Neighbor<T>> {
+    pub fn all_neighbors(&self, pos: Pos) -> Vec<Neighbor<'_, T>> {
         pos.all_neighbors()
             .into_iter()
             .filter_map(|p| self.get(p).map(|t| (p, t)))
@@ -67,18 +74,8 @@ impl<T> Map<T> {
 
 impl<T: TileDisplay> Map<T> {
     pub fn print(&self) {
-        println!("\nMap:");
-        for y in self.bounds.min.y..=self.bounds.max.y {
-            for x in self.bounds.min.x..=self.bounds.max.x {
-                let pos = Pos::new(x, y);
-                if let Some(tile) = self.get(pos) {
-                    print!("{}", tile.map_print());
-                } else {
-                    print!(".");
-                }
-            }
-            println!();
-        }
+        let options = RenderOptions::default().with_colors();
+        print!("\nMap:\n{}", self.render(&options));
     }
 }
 
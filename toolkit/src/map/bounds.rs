@@ -1,13 +1,13 @@
-use super::Pos;
+use super::{Coordinate, Pos};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Bounds {
-    pub min: Pos,
-    pub max: Pos,
+pub struct Bounds<T = i32> {
+    pub min: Pos<T>,
+    pub max: Pos<T>,
 }
 
-impl Bounds {
-    pub fn new(min: Pos, max: Pos) -> Self {
+impl<T: Coordinate> Bounds<T> {
+    pub fn new(min: Pos<T>, max: Pos<T>) -> Self {
         Self { min, max }
     }
 
@@ -18,15 +18,23 @@ impl Bounds {
         }
     }
 
-    pub fn width(&self) -> i32 {
-        self.max.x - self.min.x + 1
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::ONE
     }
 
-    pub fn height(&self) -> i32 {
-        self.max.y - self.min.y + 1
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::ONE
     }
 
-    pub fn contains(&self, pos: Pos) -> bool {
+    pub fn contains(&self, pos: Pos<T>) -> bool {
         pos.x >= self.min.x && pos.x <= self.max.x && pos.y >= self.min.y && pos.y <= self.max.y
     }
+
+    /// Converts to another coordinate type, or `None` if a corner doesn't fit
+    pub fn try_convert<U: TryFrom<T>>(self) -> Option<Bounds<U>> {
+        Some(Bounds {
+            min: self.min.try_convert()?,
+            max: self.max.try_convert()?,
+        })
+    }
 }
@@ -0,0 +1,321 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use super::Pos;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDirectionError(pub String);
+
+impl fmt::Display for ParseDirectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid direction '{}'", self.0)
+    }
+}
+
+impl Error for ParseDirectionError {}
+
+/// One of the four orthogonal directions, with `y` growing downwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction4 {
+    /// Clockwise, starting up
+    pub const ALL: [Direction4; 4] = [
+        Direction4::Up,
+        Direction4::Right,
+        Direction4::Down,
+        Direction4::Left,
+    ];
+
+    fn rotate(self, quarters: usize) -> Self {
+        Self::ALL[(self as usize + quarters) % 4]
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn turn_left(self) -> Self {
+        self.rotate(3)
+    }
+
+    pub fn turn_around(self) -> Self {
+        self.rotate(2)
+    }
+
+    pub fn turn(self, turn: Turn) -> Self {
+        match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+            Turn::Around => self.turn_around(),
+        }
+    }
+
+    /// The offset of a single step
+    pub fn offset(self) -> Pos {
+        match self {
+            Direction4::Up => Pos::N,
+            Direction4::Right => Pos::E,
+            Direction4::Down => Pos::S,
+            Direction4::Left => Pos::W,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction4::Up | Direction4::Down)
+    }
+}
+
+impl TryFrom<char> for Direction4 {
+    type Error = ParseDirectionError;
+
+    /// Parses `U/D/L/R`, `^v<>` or `N/S/E/W`
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'U' | '^' | 'N' => Ok(Direction4::Up),
+            'R' | '>' | 'E' => Ok(Direction4::Right),
+            'D' | 'v' | 'S' => Ok(Direction4::Down),
+            'L' | '<' | 'W' => Ok(Direction4::Left),
+            _ => Err(ParseDirectionError(c.to_string())),
+        }
+    }
+}
+
+impl FromStr for Direction4 {
+    type Err = ParseDirectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.trim().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Direction4::try_from(c),
+            _ => Err(ParseDirectionError(s.to_string())),
+        }
+    }
+}
+
+impl From<Direction4> for Pos {
+    fn from(direction: Direction4) -> Self {
+        direction.offset()
+    }
+}
+
+/// One of the eight compass directions, with `y` growing downwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction8 {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Direction8 {
+    /// Clockwise, starting north
+    pub const ALL: [Direction8; 8] = [
+        Direction8::N,
+        Direction8::NE,
+        Direction8::E,
+        Direction8::SE,
+        Direction8::S,
+        Direction8::SW,
+        Direction8::W,
+        Direction8::NW,
+    ];
+
+    fn rotate(self, eighths: usize) -> Self {
+        Self::ALL[(self as usize + eighths) % 8]
+    }
+
+    /// Turns 45° clockwise
+    pub fn turn_right(self) -> Self {
+        self.rotate(1)
+    }
+
+    /// Turns 45° counter-clockwise
+    pub fn turn_left(self) -> Self {
+        self.rotate(7)
+    }
+
+    pub fn turn_around(self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn offset(self) -> Pos {
+        match self {
+            Direction8::N => Pos::N,
+            Direction8::NE => Pos::NE,
+            Direction8::E => Pos::E,
+            Direction8::SE => Pos::SE,
+            Direction8::S => Pos::S,
+            Direction8::SW => Pos::SW,
+            Direction8::W => Pos::W,
+            Direction8::NW => Pos::NW,
+        }
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self as usize % 2 == 1
+    }
+}
+
+impl From<Direction4> for Direction8 {
+    fn from(direction: Direction4) -> Self {
+        Self::ALL[direction as usize * 2]
+    }
+}
+
+impl FromStr for Direction8 {
+    type Err = ParseDirectionError;
+
+    /// Parses compass points (`N`, `NE`, ...) or any orthogonal direction
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "NE" => Ok(Direction8::NE),
+            "SE" => Ok(Direction8::SE),
+            "SW" => Ok(Direction8::SW),
+            "NW" => Ok(Direction8::NW),
+            other => other
+                .parse::<Direction4>()
+                .map(Direction8::from)
+                .map_err(|_| ParseDirectionError(s.to_string())),
+        }
+    }
+}
+
+impl From<Direction8> for Pos {
+    fn from(direction: Direction8) -> Self {
+        direction.offset()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Left,
+    Right,
+    Around,
+}
+
+/// A single instruction for a `Walker`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    Forward(usize),
+    Turn(Turn),
+}
+
+impl Command {
+    /// Parses a path such as `10R5L5`: step counts and `L`, `R` or `A`
+    /// (around) turns, optionally separated by whitespace
+    pub fn parse_all(path: &str) -> Result<Vec<Command>, ParseDirectionError> {
+        let mut commands = vec![];
+        let mut steps: Option<usize> = None;
+        for c in path.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                steps = Some(steps.unwrap_or(0) * 10 + digit as usize);
+                continue;
+            }
+            commands.extend(steps.take().map(Command::Forward));
+            match c {
+                'L' => commands.push(Command::Turn(Turn::Left)),
+                'R' => commands.push(Command::Turn(Turn::Right)),
+                'A' => commands.push(Command::Turn(Turn::Around)),
+                c if c.is_whitespace() => {}
+                c => return Err(ParseDirectionError(c.to_string())),
+            }
+        }
+        commands.extend(steps.map(Command::Forward));
+        Ok(commands)
+    }
+}
+
+/// A position with a facing direction, moving forward and turning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Walker {
+    pub pos: Pos,
+    pub facing: Direction4,
+}
+
+impl Walker {
+    pub fn new(pos: Pos, facing: Direction4) -> Self {
+        Self { pos, facing }
+    }
+
+    /// The position one step ahead, without moving
+    pub fn ahead(&self) -> Pos {
+        self.pos + self.facing.offset()
+    }
+
+    pub fn forward(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.pos = self.ahead();
+        }
+    }
+
+    pub fn turn(&mut self, turn: Turn) {
+        self.facing = self.facing.turn(turn);
+    }
+
+    pub fn execute(&mut self, command: Command) {
+        match command {
+            Command::Forward(steps) => self.forward(steps),
+            Command::Turn(turn) => self.turn(turn),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        use Direction4::*;
+        assert_eq!(Up.turn_right(), Right);
+        assert_eq!(Up.turn_left(), Left);
+        assert_eq!(Left.turn_around(), Right);
+        assert_eq!(Direction8::NW.turn_right(), Direction8::N);
+        assert_eq!(Direction8::N.turn_left(), Direction8::NW);
+        assert_eq!(Direction8::from(Down), Direction8::S);
+        for direction in Direction8::ALL {
+            assert_eq!(
+                direction.turn_around().offset() + direction.offset(),
+                Pos::ZERO
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        for (input, direction) in [
+            ("U", Direction4::Up),
+            ("v", Direction4::Down),
+            ("<", Direction4::Left),
+            ("E", Direction4::Right),
+        ] {
+            assert_eq!(input.parse(), Ok(direction));
+        }
+        assert_eq!("SW".parse(), Ok(Direction8::SW));
+        assert_eq!("^".parse(), Ok(Direction8::N));
+        assert_eq!(
+            "UP".parse::<Direction4>(),
+            Err(ParseDirectionError("UP".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_walker() {
+        let mut walker = Walker::new(Pos::ZERO, Direction4::Right);
+        for command in Command::parse_all("10R5L5A2").unwrap() {
+            walker.execute(command);
+        }
+        assert_eq!(walker.pos, Pos::new(13, 5));
+        assert_eq!(walker.facing, Direction4::Left);
+        assert!(Command::parse_all("10X").is_err());
+    }
+}
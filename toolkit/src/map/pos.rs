@@ -1,28 +1,63 @@
-use std::ops::Add;
+use std::{fmt::Debug, hash::Hash, ops::Add};
 
+use num::{PrimInt, Signed};
+
+/// An integer type usable as a coordinate
+pub trait Coordinate: PrimInt + Hash + Debug {
+    const ZERO: Self;
+    const ONE: Self;
+}
+
+/// A coordinate that can go below zero, and so has directions
+pub trait SignedCoordinate: Coordinate + Signed {
+    const MINUS_ONE: Self;
+}
+
+macro_rules! coordinate {
+    ($($t:ty),*) => {
+        $(impl Coordinate for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+        })*
+    };
+}
+
+macro_rules! signed_coordinate {
+    ($($t:ty),*) => {
+        $(impl SignedCoordinate for $t {
+            const MINUS_ONE: Self = -1;
+        })*
+    };
+}
+
+coordinate!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+signed_coordinate!(i8, i16, i32, i64, i128, isize);
+
+/// A position on a grid, `i32` unless stated otherwise
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub struct Pos {
-    pub x: i32,
-    pub y: i32,
+pub struct Pos<T = i32> {
+    pub x: T,
+    pub y: T,
 }
 
-pub trait IntoPosValue {
-    fn into_coordinate(self) -> i32;
+/// Values that can be turned into a coordinate of type `T`
+pub trait IntoPosValue<T> {
+    fn into_coordinate(self) -> T;
 }
 
-impl IntoPosValue for i32 {
-    fn into_coordinate(self) -> i32 {
+impl<T: Coordinate> IntoPosValue<T> for T {
+    fn into_coordinate(self) -> T {
         self
     }
 }
 
-impl IntoPosValue for usize {
+impl IntoPosValue<i32> for usize {
     fn into_coordinate(self) -> i32 {
         self as i32
     }
 }
 
-impl Add for Pos {
+impl<T: Coordinate> Add for Pos<T> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -33,29 +68,87 @@ impl Add for Pos {
     }
 }
 
-impl Pos {
-    pub const ZERO: Pos = Pos { x: 0, y: 0 };
+impl<T: Coordinate> Pos<T> {
+    pub const ZERO: Pos<T> = Pos {
+        x: T::ZERO,
+        y: T::ZERO,
+    };
 
-    /// Directions
-    pub const NW: Pos = Pos { x: -1, y: -1 };
-    pub const N: Pos = Pos { x: 0, y: -1 };
-    pub const NE: Pos = Pos { x: 1, y: -1 };
-    pub const W: Pos = Pos { x: -1, y: 0 };
-    pub const E: Pos = Pos { x: 1, y: 0 };
-    pub const SW: Pos = Pos { x: -1, y: 1 };
-    pub const S: Pos = Pos { x: 0, y: 1 };
-    pub const SE: Pos = Pos { x: 1, y: 1 };
-
-    pub fn new<T: IntoPosValue>(x: T, y: T) -> Self {
+    pub fn new<V: IntoPosValue<T>>(x: V, y: V) -> Self {
         Self {
             x: x.into_coordinate(),
             y: y.into_coordinate(),
         }
     }
 
+    /// Converts to another coordinate type that can hold every value
+    pub fn convert<U: From<T>>(self) -> Pos<U> {
+        Pos {
+            x: self.x.into(),
+            y: self.y.into(),
+        }
+    }
+
+    /// Converts to another coordinate type, or `None` if a coordinate
+    /// doesn't fit
+    pub fn try_convert<U: TryFrom<T>>(self) -> Option<Pos<U>> {
+        Some(Pos {
+            x: self.x.try_into().ok()?,
+            y: self.y.try_into().ok()?,
+        })
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(Self {
+            x: self.x.checked_add(&rhs.x)?,
+            y: self.y.checked_add(&rhs.y)?,
+        })
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> T {
+        let diff = |a: T, b: T| if a > b { a - b } else { b - a };
+        diff(self.x, other.x) + diff(self.y, other.y)
+    }
+}
+
+impl<T: SignedCoordinate> Pos<T> {
+    /// Directions
+    pub const NW: Pos<T> = Pos {
+        x: T::MINUS_ONE,
+        y: T::MINUS_ONE,
+    };
+    pub const N: Pos<T> = Pos {
+        x: T::ZERO,
+        y: T::MINUS_ONE,
+    };
+    pub const NE: Pos<T> = Pos {
+        x: T::ONE,
+        y: T::MINUS_ONE,
+    };
+    pub const W: Pos<T> = Pos {
+        x: T::MINUS_ONE,
+        y: T::ZERO,
+    };
+    pub const E: Pos<T> = Pos {
+        x: T::ONE,
+        y: T::ZERO,
+    };
+    pub const SW: Pos<T> = Pos {
+        x: T::MINUS_ONE,
+        y: T::ONE,
+    };
+    pub const S: Pos<T> = Pos {
+        x: T::ZERO,
+        y: T::ONE,
+    };
+    pub const SE: Pos<T> = Pos {
+        x: T::ONE,
+        y: T::ONE,
+    };
+
     /// Returns only direct neighbors of this position (N, W, E, S) excluding
     /// diagonals
-    pub fn neighbors(&self) -> Vec<Pos> {
+    pub fn neighbors(&self) -> Vec<Pos<T>> {
         vec![
             // North
             *self + Self::N,
@@ -68,7 +161,7 @@ impl Pos {
     }
 
     /// Returns all neighbors of this position, including diagonals (NE, NW, SE, SW)
-    pub fn all_neighbors(&self) -> Vec<Pos> {
+    pub fn all_neighbors(&self) -> Vec<Pos<T>> {
         vec![
             // North
             *self + Self::NW,
@@ -94,4 +187,26 @@ pub mod tests {
         assert_eq!(Pos::new(1, 1) + Pos::new(1, 1), Pos::new(2, 2));
         assert_eq!(Pos::new(1, 1) + Pos::new(-1, -1), Pos::new(0, 0));
     }
+
+    #[test]
+    fn test_widths() {
+        let tall: Pos<i64> = Pos::new(3, 1_000_000_000_000);
+        assert_eq!(tall + Pos::<i64>::S, Pos::new(3, 1_000_000_000_001));
+        assert_eq!(tall.try_convert::<i32>(), None);
+        assert_eq!(Pos::new(3, -4).convert::<i64>(), Pos::new(3i64, -4));
+
+        let cell: Pos<usize> = Pos::new(2usize, 5);
+        assert_eq!(cell.try_convert::<i32>(), Some(Pos::new(2, 5)));
+        assert_eq!(Pos::new(-1, 0).try_convert::<usize>(), None);
+        assert_eq!(Pos::<u8>::new(250, 0).checked_add(Pos::new(6, 0)), None);
+        assert_eq!(cell.manhattan_distance(&Pos::new(4, 1)), 6);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let pos: Pos<i8> = Pos::new(0, 0);
+        assert_eq!(pos.neighbors().len(), 4);
+        assert!(pos.all_neighbors().contains(&Pos::new(-1, 1)));
+        assert_eq!(Pos::ZERO.neighbors(), Pos::<i32>::ZERO.neighbors());
+    }
 }
@@ -0,0 +1,172 @@
+use std::fmt::{self, Write};
+
+use super::{Bounds, Map, Pos, TileDisplay};
+
+/// How `Map::render` draws a map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Drawn where there is no tile
+    pub empty: char,
+    /// Adds the `x` coordinates above the map, written top to bottom, and
+    /// the `y` coordinates on the left
+    pub ruler: bool,
+    /// Only draws these positions, which may go past the map bounds.
+    /// `None` draws the whole map.
+    pub viewport: Option<Bounds>,
+    /// Keeps the terminal colors of `TileDisplay`
+    pub colors: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            empty: '.',
+            ruler: false,
+            viewport: None,
+            colors: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn with_empty(self, empty: char) -> Self {
+        Self { empty, ..self }
+    }
+
+    pub fn with_ruler(self) -> Self {
+        Self {
+            ruler: true,
+            ..self
+        }
+    }
+
+    pub fn with_viewport(self, viewport: Bounds) -> Self {
+        Self {
+            viewport: Some(viewport),
+            ..self
+        }
+    }
+
+    pub fn with_colors(self) -> Self {
+        Self {
+            colors: true,
+            ..self
+        }
+    }
+}
+
+/// Removes terminal escape sequences, such as the colors added by `colored`
+pub(crate) fn strip_ansi(s: &str) -> String {
+    let mut result = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skips the escape sequence up to its final letter, as in `\x1b[32m`
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+impl<T: TileDisplay> Map<T> {
+    /// Draws the map one line per row, each line ending with a newline
+    pub fn render_to(&self, out: &mut impl Write, options: &RenderOptions) -> fmt::Result {
+        let bounds = options.viewport.unwrap_or(self.bounds);
+        let margin = if options.ruler {
+            let width = |y: i32| y.to_string().len();
+            width(bounds.min.y).max(width(bounds.max.y)) + 1
+        } else {
+            0
+        };
+
+        if options.ruler {
+            let labels: Vec<String> = (bounds.min.x..=bounds.max.x)
+                .map(|x| x.to_string())
+                .collect();
+            let rows = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+            for row in 0..rows {
+                write!(out, "{:margin$}", "")?;
+                for label in labels.iter() {
+                    // Labels are aligned on their last digit
+                    let skip = rows - label.len();
+                    let c = match row.checked_sub(skip) {
+                        Some(i) => label.as_bytes()[i] as char,
+                        None => ' ',
+                    };
+                    out.write_char(c)?;
+                }
+                writeln!(out)?;
+            }
+        }
+
+        for y in bounds.min.y..=bounds.max.y {
+            if options.ruler {
+                write!(out, "{:>width$} ", y, width = margin - 1)?;
+            }
+            for x in bounds.min.x..=bounds.max.x {
+                match self.get(Pos::new(x, y)) {
+                    Some(tile) if options.colors => write!(out, "{}", tile.map_print())?,
+                    Some(tile) => out.write_str(&strip_ansi(&tile.map_print().to_string()))?,
+                    None => out.write_char(options.empty)?,
+                }
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let mut out = String::new();
+        self.render_to(&mut out, options)
+            .expect("Writing to a String can't fail");
+        out
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{golden::assert_golden, map::Tile};
+
+    #[derive(Tile, Debug, Clone, Copy, PartialEq, Eq)]
+    enum Tile {
+        #[tile('#', color = "green")]
+        Rock,
+        #[tile('o', color = "yellow")]
+        Sand,
+    }
+
+    fn map() -> Map<Tile> {
+        Map::from_tiles("..o..\n.#.#.\n#####", '.').unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let map = map();
+        assert_eq!(
+            map.render(&RenderOptions::default()),
+            "..o..\n.#.#.\n#####\n"
+        );
+        let options = RenderOptions::default()
+            .with_empty(' ')
+            .with_viewport(Bounds::new(Pos::new(1, 0), Pos::new(6, 1)));
+        assert_eq!(map.render(&options), " o    \n# #   \n");
+
+        let mut out = String::from("Map:\n");
+        map.render_to(&mut out, &RenderOptions::default()).unwrap();
+        assert!(out.starts_with("Map:\n..o.."));
+    }
+
+    #[test]
+    fn test_ruler() {
+        let mut map = map();
+        map.tiles.insert(Pos::new(-3, 11), Tile::Sand);
+        map.update_bounds();
+        assert_golden(
+            "golden/ruler.txt",
+            &map.render(&RenderOptions::default().with_ruler()),
+        );
+    }
+}
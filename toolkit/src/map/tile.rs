@@ -0,0 +1,195 @@
+use std::{error::Error, fmt};
+
+use colored::Colorize;
+
+use super::{Bounds, Map, Pos};
+
+/**
+ * A tile written as a single character on a map. Usually derived with
+ * `#[derive(Tile)]`, which also implements `TileDisplay`:
+ *
+ * ```
+ * use toolkit::map::{Map, Tile, TileChar};
+ *
+ * #[derive(Tile, Debug, Clone, Copy, PartialEq, Eq)]
+ * enum Cell {
+ *     #[tile('#', color = "green")]
+ *     Elf,
+ *     #[tile('o', color = "red", background = "black")]
+ *     Rock,
+ * }
+ *
+ * let map: Map<Cell> = Map::from_tiles("#.\n.o", '.').unwrap();
+ * assert_eq!(map.tiles.len(), 2);
+ * assert_eq!(map.to_tiles('.'), "#.\n.o");
+ * assert_eq!(Cell::Rock.to_char(), 'o');
+ * ```
+ *
+ * Two variants can't share a character:
+ *
+ * ```compile_fail
+ * use toolkit::map::Tile;
+ *
+ * #[derive(Tile)]
+ * enum Cell {
+ *     #[tile('#')]
+ *     Wall,
+ *     #[tile('#')]
+ *     Rock,
+ * }
+ * ```
+ *
+ * Colors must be names known by `colored`:
+ *
+ * ```compile_fail
+ * use toolkit::map::Tile;
+ *
+ * #[derive(Tile)]
+ * enum Cell {
+ *     #[tile('#', color = "grene")]
+ *     Wall,
+ * }
+ * ```
+ */
+pub trait TileChar: Sized {
+    fn from_char(c: char) -> Option<Self>;
+    fn to_char(&self) -> char;
+}
+
+/// A tile character painted with `colored` color names, used by the
+/// `TileDisplay` implementations generated by `#[derive(Tile)]`, which
+/// checks the names at compile time
+pub fn colored_tile(
+    c: char,
+    color: Option<&str>,
+    background: Option<&str>,
+) -> Box<dyn fmt::Display> {
+    let mut tile = c.to_string().normal();
+    if let Some(color) = color {
+        tile = tile.color(color);
+    }
+    if let Some(background) = background {
+        tile = tile.on_color(background);
+    }
+    Box::new(tile)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTileError {
+    pub c: char,
+    pub pos: Pos,
+}
+
+impl fmt::Display for ParseTileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown tile '{}' at {}, {}",
+            self.c, self.pos.x, self.pos.y
+        )
+    }
+}
+
+impl Error for ParseTileError {}
+
+impl<T: TileChar> Map<T> {
+    /// Parses a map where `empty` marks positions without a tile, even if a
+    /// tile is also written with that character. The bounds cover the whole
+    /// input, including empty rows and columns on its borders.
+    pub fn from_tiles(input: &str, empty: char) -> Result<Self, ParseTileError> {
+        let mut error = None;
+        let mut map = Self::parse(input, |c, x, y| {
+            if c == empty {
+                return None;
+            }
+            let tile = T::from_char(c);
+            if tile.is_none() && error.is_none() {
+                error = Some(ParseTileError {
+                    c,
+                    pos: Pos::new(x, y),
+                });
+            }
+            tile
+        });
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        // An empty input gives bounds without any position
+        let width = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let height = input.lines().count();
+        map.bounds = Bounds::new(Pos::ZERO, Pos::new(width as i32 - 1, height as i32 - 1));
+        Ok(map)
+    }
+
+    /// Writes the map back as parsed by `from_tiles`, over its bounds
+    pub fn to_tiles(&self, empty: char) -> String {
+        (self.bounds.min.y..=self.bounds.max.y)
+            .map(|y| {
+                (self.bounds.min.x..=self.bounds.max.x)
+                    .map(|x| self.get(Pos::new(x, y)).map_or(empty, T::to_char))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::map::{Tile, TileDisplay};
+
+    #[derive(Tile, Debug, Clone, Copy, PartialEq, Eq)]
+    enum Tile {
+        #[tile('#')]
+        Wall,
+        #[tile('.', color = "white", background = "black")]
+        Open,
+        #[tile('>', color = "green")]
+        Blizzard,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = "#.>\n ##";
+        let map: Map<Tile> = Map::from_tiles(input, ' ').unwrap();
+        assert_eq!(map[Pos::new(1, 0)], Tile::Open);
+        assert_eq!(map.get(Pos::new(0, 1)), None);
+        assert_eq!(map.to_tiles(' '), input);
+        assert_eq!(Tile::from_char('>'), Some(Tile::Blizzard));
+        assert_eq!(Tile::from_char('x'), None);
+    }
+
+    #[test]
+    fn test_empty_borders() {
+        let input = "....\n..#.\n....";
+        let map: Map<Tile> = Map::from_tiles(input, '.').unwrap();
+        assert_eq!(map.tiles.len(), 1);
+        assert_eq!(map.to_tiles('.'), input);
+
+        let map: Map<Tile> = Map::from_tiles("", '.').unwrap();
+        assert_eq!(map.bounds.width(), 0);
+        assert_eq!(map.to_tiles('.'), "");
+        let map: Map<Tile> = Map::from_tiles("..\n.", '.').unwrap();
+        assert_eq!(map.to_tiles('.'), "..\n..");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Map::<Tile>::from_tiles("##\n#x", '.').unwrap_err(),
+            ParseTileError {
+                c: 'x',
+                pos: Pos::new(1, 1)
+            }
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        colored::control::set_override(false);
+        assert_eq!(Tile::Blizzard.map_print().to_string(), ">");
+        colored::control::unset_override();
+    }
+}